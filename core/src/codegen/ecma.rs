@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str::FromStr;
@@ -6,26 +6,27 @@ use std::str::FromStr;
 use num_bigint::BigInt as BigIntValue;
 use swc_common::{BytePos, SourceMap, Span, DUMMY_SP};
 use swc_ecma_ast::{
-    ArrowExpr, AssignExpr, AssignOp, BigInt as JsBigInt, BinExpr, BinaryOp, BindingIdent,
-    BlockStmt, BlockStmtOrExpr, Bool, CallExpr, Callee, ComputedPropName, CondExpr, Decl,
-    ExportDecl, Expr, ExprOrSpread, ExprStmt, FnDecl, Function, Ident, ImportDecl,
-    ImportNamedSpecifier, ImportSpecifier, ImportStarAsSpecifier, KeyValueProp, Lit, MemberExpr,
-    MemberProp, Module, ModuleDecl, ModuleItem, Number as JsNumber, ObjectLit, Param as JsParam,
-    ParenExpr, Pat, PatOrExpr, Prop, PropName, PropOrSpread, ReturnStmt, SpreadElement, Stmt,
-    Str as JsStr, VarDecl, VarDeclKind, VarDeclarator,
+    ArrayLit, ArrayPat, ArrowExpr, AssignExpr, AssignOp, BigInt as JsBigInt, BinExpr, BinaryOp,
+    BindingIdent, BlockStmt, BlockStmtOrExpr, Bool, CallExpr, Callee, ComputedPropName, CondExpr,
+    Decl, ExportDecl, Expr, ExprOrSpread, ExprStmt, FnDecl, Function, Ident, ImportDecl,
+    ImportNamedSpecifier, ImportSpecifier, ImportStarAsSpecifier, KeyValuePatProp, KeyValueProp,
+    Lit, MemberExpr, MemberProp, Module, ModuleDecl, ModuleItem, Number as JsNumber, ObjectLit,
+    ObjectPat, ObjectPatProp, Param as JsParam, ParenExpr, Pat, PatOrExpr, Prop, PropName,
+    PropOrSpread, RestPat, ReturnStmt, SpreadElement, Stmt, Str as JsStr, VarDecl, VarDeclKind,
+    VarDeclarator,
 };
 use swc_ecma_codegen::text_writer::JsWriter;
 use swc_ecma_codegen::Emitter;
 
 use crate::codegen::{mangle_hkt, Target};
 use crate::theory::abs::data::Term;
-use crate::theory::abs::def::{Body, Def, Sigma};
+use crate::theory::abs::def::{super_path, Body, Def, ImplementsBody, Sigma};
 use crate::theory::conc::data::ArgInfo;
 use crate::theory::conc::data::ArgInfo::UnnamedExplicit;
 use crate::theory::conc::load::{Import, ImportedDefs, ImportedPkg, ModuleID};
 use crate::theory::ParamInfo::Explicit;
 use crate::theory::{Loc, Param, Tele, Var, THIS, TUPLED, UNBOUND, UNTUPLED_RHS, VPTR};
-use crate::Error::{NonErasable, UnsolvedMeta};
+use crate::Error::{FieldsUnknown, NonErasable, UnresolvedImplementation, UnsolvedMeta};
 use crate::{Error, ModuleFile};
 
 impl From<Loc> for Span {
@@ -44,12 +45,26 @@ const JS_ESCAPED_THIS: &str = "__this";
 const JS_ENUM_TAG: &str = "__enumT";
 const JS_ENUM_VAL: &str = "__enumV";
 const JS_VTBL: &str = "__vtbl";
+const JS_DICT: &str = "__dict";
+const JS_SELF_DICT: &str = "__self";
+const JS_OMITTED: &str = "__omitted";
+const JS_REST: &str = "__rest";
+const JS_OPTIONAL: &str = "__optional";
+const JS_MAPPED: &str = "__mapped";
+const JS_KEY: &str = "__key";
 
 type Vtbl = HashMap<String, Vec<(String, Var)>>;
 
 #[derive(Default)]
 pub struct Ecma {
     vtbl: Vtbl,
+    preds: Vec<(Var, Vec<Term>, Var)>,
+    module: ModuleID,
+    emitted: HashMap<Var, ModuleID>,
+    exports: HashMap<ModuleID, HashSet<String>>,
+    defaults: HashSet<Var>,
+    imported: HashSet<Var>,
+    dict_imports: Vec<(ModuleID, Var)>,
 }
 
 impl Ecma {
@@ -77,6 +92,145 @@ impl Ecma {
         Self::str_ident(loc, v.as_str())
     }
 
+    fn mangled_ident(loc: Loc, v: &Var) -> Ident {
+        let s = v
+            .as_str()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match c {
+                c if c.is_alphanumeric() || c == '_' || c == '$' => c,
+                '{' | '}' => '$',
+                _ => '_',
+            })
+            .collect::<String>();
+        Self::str_ident(loc, s.as_str())
+    }
+
+    fn dict_ident(loc: Loc, i: usize) -> Ident {
+        Self::str_ident(loc, format!("{JS_DICT}{i}").as_str())
+    }
+
+    fn dict_params(loc: Loc, n: usize) -> Vec<JsParam> {
+        (0..n)
+            .map(|i| JsParam {
+                span: loc.into(),
+                decorators: Default::default(),
+                pat: Pat::Ident(BindingIdent {
+                    id: Self::dict_ident(loc, i),
+                    type_ann: None,
+                }),
+            })
+            .collect()
+    }
+
+    fn preds(tele: &Tele<Term>) -> Vec<(Var, Vec<Term>, Var)> {
+        tele.iter()
+            .filter_map(|p| match p.typ.as_ref() {
+                Term::ImplementsOf(ts, i) => Some((p.var.clone(), ts.clone(), i.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn is_same_type(a: &Term, b: &Term) -> bool {
+        use Term::*;
+
+        fn all_same(xs: &[Term], ys: &[Term]) -> bool {
+            xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| Ecma::is_same_type(x, y))
+        }
+
+        match (a, b) {
+            (Ref(x), Ref(y)) | (Undef(x), Undef(y)) => x == y,
+            (Qualified(m, x), Qualified(n, y)) => m == n && x == y,
+            (App(f, _, x), App(g, _, y)) => Self::is_same_type(f, g) && Self::is_same_type(x, y),
+            (Pi(p, b), Pi(q, c)) | (Sigma(p, b), Sigma(q, c)) => {
+                Self::is_same_type(&p.typ, &q.typ) && Self::is_same_type(b, c)
+            }
+            (Array(x), Array(y))
+            | (Object(x), Object(y))
+            | (Enum(x), Enum(y))
+            | (Optional(x), Optional(y))
+            | (Singleton(x), Singleton(y)) => Self::is_same_type(x, y),
+            (Fields(x), Fields(y)) => {
+                x.len() == y.len()
+                    && x.iter()
+                        .all(|(n, x)| y.get(n).is_some_and(|y| Self::is_same_type(x, y)))
+            }
+            (Union(xs), Union(ys)) => all_same(xs, ys),
+            (Nominal(x, xs), Nominal(y, ys)) | (Vptr(x, xs), Vptr(y, ys)) => {
+                x == y && all_same(xs, ys)
+            }
+            (Str(x), Str(y)) => x == y,
+            (Num(x), Num(y)) => x == y,
+            (Univ, Univ)
+            | (Unit, Unit)
+            | (Boolean, Boolean)
+            | (String, String)
+            | (Number, Number)
+            | (BigInt, BigInt)
+            | (Row, Row) => true,
+            _ => false,
+        }
+    }
+
+    /// Finds the dictionary satisfying the constraint that `ts` implement `i`, either a dictionary
+    /// parameter or a superinterface dictionary reachable from one.
+    fn dict_of(&self, sigma: &Sigma, loc: Loc, ts: &[Term], i: &Var) -> Option<Expr> {
+        self.preds
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, xs, _))| {
+                xs.len() == ts.len() && xs.iter().zip(ts).all(|(x, t)| Self::is_same_type(x, t))
+            })
+            .find_map(|(d, (_, _, j))| {
+                let path = match j == i {
+                    true => Vec::default(),
+                    false => super_path(sigma, j, i)?,
+                };
+                Some(
+                    path.into_iter()
+                        .fold(Expr::Ident(Self::dict_ident(loc, d)), |obj, s| {
+                            Expr::Member(MemberExpr {
+                                span: loc.into(),
+                                obj: Box::new(obj),
+                                prop: MemberProp::Ident(Self::str_ident(loc, &s.super_dict())),
+                            })
+                        }),
+                )
+            })
+    }
+
+    fn dict_of_scope(&self, sigma: &Sigma, loc: Loc, ts: &[Term], i: &Var) -> Result<Expr, Error> {
+        self.dict_of(sigma, loc, ts, i).ok_or_else(|| {
+            UnresolvedImplementation(
+                i.clone(),
+                ts.iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                loc,
+            )
+        })
+    }
+
+    fn emit(&mut self, v: &Var) {
+        self.emitted.insert(v.clone(), self.module.clone());
+        self.exports
+            .entry(self.module.clone())
+            .or_default()
+            .insert(v.to_string());
+    }
+
+    /// References a definition emitted so far, importing it by its mangled name if it is emitted by
+    /// another module.
+    fn emitted_ref(&mut self, loc: Loc, v: &Var) -> Option<Expr> {
+        let m = self.emitted.get(v)?;
+        if m != &self.module && self.imported.insert(v.clone()) {
+            self.dict_imports.push((m.clone(), v.clone()));
+        }
+        Some(Expr::Ident(Self::mangled_ident(loc, v)))
+    }
+
     fn ident_pat(loc: Loc, v: &Var) -> Pat {
         Pat::Ident(BindingIdent {
             id: Self::ident(loc, v),
@@ -84,6 +238,30 @@ impl Ecma {
         })
     }
 
+    /// Multiple payloads of a variant are bound to a tupled variable, which is destructured by the
+    /// chain of tuple lets starting the case body.
+    fn case_pat(loc: Loc, v: &Var, mut tm: &Term) -> Pat {
+        if v.as_str() != TUPLED {
+            return Self::ident_pat(loc, v);
+        }
+        let mut elems = Vec::default();
+        let mut x = v;
+        while let Term::TupleLet(p, q, a, b) = tm {
+            if !matches!(a.as_ref(), Term::Ref(r) if r == x) {
+                break;
+            }
+            elems.push(Some(Self::ident_pat(loc, &p.var)));
+            x = &q.var;
+            tm = b;
+        }
+        Pat::Array(ArrayPat {
+            span: loc.into(),
+            elems,
+            optional: false,
+            type_ann: None,
+        })
+    }
+
     fn undefined() -> Ident {
         Self::special_ident("undefined")
     }
@@ -115,27 +293,42 @@ impl Ecma {
         }))
     }
 
-    fn app(
+    fn app<'a>(
         &mut self,
         sigma: &Sigma,
         loc: Loc,
-        mut f: &Term,
-        i: &ArgInfo,
-        x: &Term,
+        mut f: &'a Term,
+        mut i: &'a ArgInfo,
+        mut x: &'a Term,
     ) -> Result<Expr, Error> {
         use Term::*;
-        if !matches!(i, UnnamedExplicit) {
-            unreachable!()
+        let mut trailing = Vec::default();
+        while !matches!(i, UnnamedExplicit) {
+            trailing.push(x);
+            match f {
+                App(ff, ii, xx) => {
+                    f = ff;
+                    i = ii;
+                    x = xx;
+                }
+                _ => unreachable!(),
+            }
         }
+        let mut spine = trailing;
+        spine.push(x);
         loop {
-            if let App(ff, ii, _) = f {
+            if let App(ff, ii, xx) = f {
                 if !matches!(ii, UnnamedExplicit) {
+                    spine.push(xx.as_ref());
                     f = ff;
                     continue;
                 }
             }
             break;
         }
+        spine.reverse();
+        let mut args = self.dict_args(sigma, loc, f, &spine)?;
+        args.extend(self.untuple_args(sigma, loc, x)?);
         let callee = match f {
             Access(l, m) => match l.as_ref() {
                 Lookup(_) => {
@@ -186,11 +379,39 @@ impl Ecma {
         Ok(Expr::Call(CallExpr {
             span: loc.into(),
             callee: Callee::Expr(Box::new(callee)),
-            args: self.untuple_args(sigma, loc, x)?,
+            args,
             type_args: None,
         }))
     }
 
+    /// Picks the dictionaries out of the arguments applied to a constrained function.
+    fn dict_args(
+        &mut self,
+        sigma: &Sigma,
+        loc: Loc,
+        f: &Term,
+        spine: &[&Term],
+    ) -> Result<Vec<ExprOrSpread>, Error> {
+        use Term::*;
+        let tele = match f {
+            Ref(v) | Undef(v) | Qualified(_, v) => match sigma.get(v) {
+                Some(d) => &d.tele,
+                None => return Ok(Default::default()),
+            },
+            _ => return Ok(Default::default()),
+        };
+        let mut args = Vec::default();
+        for (p, x) in tele.iter().zip(spine) {
+            if let ImplementsOf(_, _) = p.typ.as_ref() {
+                args.push(ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(self.expr(sigma, loc, x)?),
+                });
+            }
+        }
+        Ok(args)
+    }
+
     fn bin_expr(
         &mut self,
         sigma: &Sigma,
@@ -208,11 +429,16 @@ impl Ecma {
     }
 
     fn func(&mut self, sigma: &Sigma, def: &Def<Term>, body: &Term) -> Result<Function, Error> {
+        self.preds = Self::preds(&def.tele);
+        let mut params = Self::dict_params(def.loc, self.preds.len());
+        params.extend(Self::type_erased_params(def.loc, &def.tele));
+        let body = self.block(sigma, def.loc, body);
+        self.preds.clear();
         Ok(Function {
-            params: Self::type_erased_params(def.loc, &def.tele),
+            params,
             decorators: Default::default(),
             span: def.loc.into(),
-            body: Some(self.block(sigma, def.loc, body)?),
+            body: Some(body?),
             is_generator: false,
             is_async: false,
             type_params: None,
@@ -307,18 +533,24 @@ impl Ecma {
         &mut self,
         sigma: &Sigma,
         loc: Loc,
-        v: Option<&Var>,
+        p: Option<Pat>,
         a: &Term,
         b: &Term,
     ) -> Result<Expr, Error> {
-        Ok(Expr::Call(CallExpr {
+        let b = self.expr(sigma, loc, b)?;
+        let a = self.expr(sigma, loc, a)?;
+        Ok(Self::arrow_call(loc, p, b, a))
+    }
+
+    fn arrow_call(loc: Loc, p: Option<Pat>, body: Expr, arg: Expr) -> Expr {
+        Expr::Call(CallExpr {
             span: loc.into(),
             callee: Callee::Expr(Box::new(Expr::Paren(ParenExpr {
                 span: loc.into(),
                 expr: Box::new(Expr::Arrow(ArrowExpr {
                     span: loc.into(),
-                    params: v.map_or_else(Default::default, |v| vec![Self::ident_pat(loc, v)]),
-                    body: Box::new(BlockStmtOrExpr::Expr(Box::new(self.expr(sigma, loc, b)?))),
+                    params: p.map_or_else(Default::default, |p| vec![p]),
+                    body: Box::new(BlockStmtOrExpr::Expr(Box::new(body))),
                     is_async: false,
                     is_generator: false,
                     type_params: None,
@@ -327,17 +559,92 @@ impl Ecma {
             }))),
             args: vec![ExprOrSpread {
                 spread: None,
-                expr: Box::new(self.expr(sigma, loc, a)?),
+                expr: Box::new(arg),
             }],
             type_args: None,
-        }))
+        })
+    }
+
+    fn method_call(loc: Loc, obj: Expr, m: Ident, args: Vec<Expr>) -> Expr {
+        Expr::Call(CallExpr {
+            span: loc.into(),
+            callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+                span: loc.into(),
+                obj: Box::new(obj),
+                prop: MemberProp::Ident(m),
+            }))),
+            args: args
+                .into_iter()
+                .map(|a| ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(a),
+                })
+                .collect(),
+            type_args: None,
+        })
+    }
+
+    fn variant(loc: Loc, name: &str, val: Expr) -> Expr {
+        Expr::Object(ObjectLit {
+            span: loc.into(),
+            props: vec![
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(Self::str_ident(loc, JS_ENUM_TAG)),
+                    value: Box::new(Expr::Lit(Lit::Str(JsStr {
+                        span: loc.into(),
+                        value: name.into(),
+                        raw: None,
+                    }))),
+                }))),
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(Self::str_ident(loc, JS_ENUM_VAL)),
+                    value: Box::new(val),
+                }))),
+            ],
+        })
+    }
+
+    fn object_pat(loc: Loc, fields: Vec<(&str, Ident)>, rest: Option<Ident>) -> Pat {
+        let mut props = fields
+            .into_iter()
+            .map(|(n, id)| {
+                ObjectPatProp::KeyValue(KeyValuePatProp {
+                    key: PropName::Ident(Self::str_ident(loc, n)),
+                    value: Box::new(Pat::Ident(BindingIdent { id, type_ann: None })),
+                })
+            })
+            .collect::<Vec<_>>();
+        if let Some(id) = rest {
+            props.push(ObjectPatProp::Rest(RestPat {
+                span: loc.into(),
+                dot3_token: loc.into(),
+                arg: Box::new(Pat::Ident(BindingIdent { id, type_ann: None })),
+                type_ann: None,
+            }));
+        }
+        Pat::Object(ObjectPat {
+            span: loc.into(),
+            props,
+            optional: false,
+            type_ann: None,
+        })
+    }
+
+    fn object_let_pat(loc: Loc, ps: &[(String, Param<Term>)], rest: &Option<Param<Term>>) -> Pat {
+        Self::object_pat(
+            loc,
+            ps.iter()
+                .map(|(n, p)| (n.as_str(), Self::ident(loc, &p.var)))
+                .collect(),
+            rest.as_ref().map(|p| Self::ident(loc, &p.var)),
+        )
     }
 
     fn const_decl_stmt(
         &mut self,
         sigma: &Sigma,
         loc: Loc,
-        v: &Var,
+        pat: Pat,
         tm: &Term,
     ) -> Result<Stmt, Error> {
         Ok(Stmt::Decl(Decl::Var(Box::new(VarDecl {
@@ -346,13 +653,36 @@ impl Ecma {
             declare: false,
             decls: vec![VarDeclarator {
                 span: loc.into(),
-                name: Self::ident_pat(loc, v),
+                name: pat,
                 init: Some(Box::new(self.expr(sigma, loc, tm)?)),
                 definite: false,
             }],
         }))))
     }
 
+    /// Emits a local function as a function declaration, so that it could refer to itself.
+    fn local_fn_stmt(&mut self, sigma: &Sigma, loc: Loc, v: &Var, f: &Term) -> Result<Stmt, Error> {
+        let mut def = sigma.get(v).unwrap().clone();
+        let mut body = f;
+        for p in def.tele.iter_mut() {
+            body = match body {
+                Term::Lam(q, b) => {
+                    *p = q.clone();
+                    b
+                }
+                _ => unreachable!(),
+            };
+        }
+        let preds = std::mem::take(&mut self.preds);
+        let function = self.func(sigma, &def, body);
+        self.preds = preds;
+        Ok(Stmt::Decl(Decl::Fn(FnDecl {
+            ident: Self::ident(loc, v),
+            declare: false,
+            function: Box::new(function?),
+        })))
+    }
+
     fn unit_stmt(&mut self, sigma: &Sigma, loc: Loc, tm: &Term) -> Result<Stmt, Error> {
         Ok(Stmt::Expr(ExprStmt {
             span: loc.into(),
@@ -380,7 +710,17 @@ impl Ecma {
         loop {
             match tm {
                 Let(p, a, b) => {
-                    stmts.push(self.const_decl_stmt(sigma, loc, &p.var, a)?);
+                    stmts.push(self.const_decl_stmt(
+                        sigma,
+                        loc,
+                        Self::ident_pat(loc, &p.var),
+                        a,
+                    )?);
+                    tm = b
+                }
+                ObjectLet(ps, rest, a, b) => {
+                    let pat = Self::object_let_pat(loc, ps, rest);
+                    stmts.push(self.const_decl_stmt(sigma, loc, pat, a)?);
                     tm = b
                 }
                 TupleLet(_, _, _, _) => unreachable!(),
@@ -388,6 +728,10 @@ impl Ecma {
                     stmts.push(self.unit_stmt(sigma, loc, a)?);
                     tm = b
                 }
+                LocalFn(v, f, b) => {
+                    stmts.push(self.local_fn_stmt(sigma, loc, v, f)?);
+                    tm = b
+                }
                 _ => {
                     stmts.push(Stmt::Return(ReturnStmt {
                         span: loc.into(),
@@ -417,15 +761,45 @@ impl Ecma {
                 _ => unreachable!(),
             },
 
-            Let(p, a, b) => self.lambda_encoded_let(sigma, loc, Some(&p.var), a, b)?,
+            Let(p, a, b) => {
+                self.lambda_encoded_let(sigma, loc, Some(Self::ident_pat(loc, &p.var)), a, b)?
+            }
+            ObjectLet(ps, rest, a, b) => {
+                let pat = Self::object_let_pat(loc, ps, rest);
+                self.lambda_encoded_let(sigma, loc, Some(pat), a, b)?
+            }
             UnitLet(a, b) => self.lambda_encoded_let(sigma, loc, None, a, b)?,
-
-            Ref(r) | Undef(r) => Expr::Ident(Self::ident(loc, r)),
-            Extern(r) => Expr::Member(MemberExpr {
+            LocalFn(_, _, _) => Expr::Call(CallExpr {
                 span: loc.into(),
-                obj: Box::new(Expr::Ident(Self::lib())),
-                prop: MemberProp::Ident(Self::ident(loc, r)),
+                callee: Callee::Expr(Box::new(Expr::Paren(ParenExpr {
+                    span: loc.into(),
+                    expr: Box::new(Expr::Arrow(ArrowExpr {
+                        span: loc.into(),
+                        params: Default::default(),
+                        body: Box::new(BlockStmtOrExpr::BlockStmt(self.block(sigma, loc, tm)?)),
+                        is_async: false,
+                        is_generator: false,
+                        type_params: None,
+                        return_type: None,
+                    })),
+                }))),
+                args: Default::default(),
+                type_args: None,
             }),
+
+            Ref(r) | Undef(r) => match self.preds.iter().position(|(v, _, _)| v == r) {
+                Some(i) => Expr::Ident(Self::dict_ident(loc, i)),
+                None => Expr::Ident(Self::ident(loc, r)),
+            },
+            Extern(r) => match self.emitted.get(r) {
+                // Unfolded implementations could refer to the externals of their own modules.
+                Some(m) if m != &self.module => self.emitted_ref(loc, r).unwrap(),
+                _ => Expr::Member(MemberExpr {
+                    span: loc.into(),
+                    obj: Box::new(Expr::Ident(Self::lib())),
+                    prop: MemberProp::Ident(Self::ident(loc, r)),
+                }),
+            },
             Qualified(m, r) => Expr::Member(MemberExpr {
                 span: loc.into(),
                 obj: Box::new(Expr::Ident(Self::str_ident(
@@ -473,6 +847,13 @@ impl Ecma {
                 value: *v,
                 raw: None,
             })),
+            StrAdd(a, b) => self.bin_expr(sigma, loc, BinaryOp::Add, a, b)?,
+            StrQuote(a) => Self::method_call(
+                loc,
+                Expr::Ident(Self::special_ident("JSON")),
+                Self::special_ident("stringify"),
+                vec![self.expr(sigma, loc, a)?],
+            ),
             NumAdd(a, b) => self.bin_expr(sigma, loc, BinaryOp::Add, a, b)?,
             NumSub(a, b) => self.bin_expr(sigma, loc, BinaryOp::Sub, a, b)?,
             Big(v) => Expr::Lit(Lit::BigInt(JsBigInt {
@@ -480,6 +861,28 @@ impl Ecma {
                 value: Box::new(BigIntValue::from_str(v).unwrap()),
                 raw: None,
             })),
+            Arr(xs) => {
+                let mut elems = Vec::default();
+                for x in xs {
+                    elems.push(Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(self.expr(sigma, loc, x)?),
+                    }));
+                }
+                Expr::Array(ArrayLit {
+                    span: loc.into(),
+                    elems,
+                })
+            }
+            FieldNames(r) => return Err(FieldsUnknown(*r.clone(), loc)),
+            Tuple(_, _) => Expr::Array(ArrayLit {
+                span: loc.into(),
+                elems: self
+                    .untuple_args(sigma, loc, tm)?
+                    .into_iter()
+                    .map(Some)
+                    .collect(),
+            }),
             Obj(f) => match f.as_ref() {
                 Fields(fields) => {
                     let mut props = Vec::default();
@@ -513,6 +916,28 @@ impl Ecma {
                 ],
             }),
             Access(a, n) => self.access(sigma, loc, a, n)?,
+            Update(a, f) => match f.as_ref() {
+                Fields(fields) => {
+                    let mut props = vec![PropOrSpread::Spread(SpreadElement {
+                        dot3_token: loc.into(),
+                        expr: Box::new(self.expr(sigma, loc, a)?),
+                    })];
+                    for (name, tm) in fields {
+                        props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                            key: PropName::Ident(Self::str_ident(loc, name.as_str())),
+                            value: Box::new(self.expr(sigma, loc, tm)?),
+                        }))));
+                    }
+                    Expr::Paren(ParenExpr {
+                        span: loc.into(),
+                        expr: Box::new(Expr::Object(ObjectLit {
+                            span: loc.into(),
+                            props,
+                        })),
+                    })
+                }
+                _ => unreachable!(),
+            },
             Downcast(a, f) => match f.as_ref() {
                 Fields(fields) => {
                     let mut props = Vec::default();
@@ -529,26 +954,126 @@ impl Ecma {
                 }
                 _ => unreachable!(),
             },
+            Restrict(a, ns) => {
+                // (({x: __omitted0, ...__rest}) => __rest)(a)
+                let fields = ns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, n)| {
+                        let omitted = format!("{JS_OMITTED}{i}");
+                        (n.as_str(), Self::str_ident(loc, omitted.as_str()))
+                    })
+                    .collect::<Vec<_>>();
+                let pat = Self::object_pat(loc, fields, Some(Self::str_ident(loc, JS_REST)));
+                let a = self.expr(sigma, loc, a)?;
+                Self::arrow_call(
+                    loc,
+                    Some(pat),
+                    Expr::Ident(Self::str_ident(loc, JS_REST)),
+                    a,
+                )
+            }
+            MapMethod(a, r, i, f) => {
+                // (__mapped => Object.fromEntries(Object.keys(d).map(__key =>
+                //   [__key, d[__key].f(__mapped[__key])])))(a)
+                let dict = self.dict_of_scope(sigma, loc, &[*r.clone()], i)?;
+                let key = || Box::new(Expr::Ident(Self::str_ident(loc, JS_KEY)));
+                let computed = |obj: Expr| {
+                    Expr::Member(MemberExpr {
+                        span: loc.into(),
+                        obj: Box::new(obj),
+                        prop: MemberProp::Computed(ComputedPropName {
+                            span: loc.into(),
+                            expr: key(),
+                        }),
+                    })
+                };
+                let call = Self::method_call(
+                    loc,
+                    computed(dict.clone()),
+                    Self::ident(loc, f),
+                    vec![computed(Expr::Ident(Self::str_ident(loc, JS_MAPPED)))],
+                );
+                let entry = Expr::Array(ArrayLit {
+                    span: loc.into(),
+                    elems: vec![
+                        Some(ExprOrSpread {
+                            spread: None,
+                            expr: key(),
+                        }),
+                        Some(ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(call),
+                        }),
+                    ],
+                });
+                let keys = Self::method_call(
+                    loc,
+                    Expr::Ident(Self::special_ident("Object")),
+                    Self::special_ident("keys"),
+                    vec![dict],
+                );
+                let entries = Self::method_call(
+                    loc,
+                    keys,
+                    Self::special_ident("map"),
+                    vec![Expr::Arrow(ArrowExpr {
+                        span: loc.into(),
+                        params: vec![Pat::Ident(BindingIdent {
+                            id: Self::str_ident(loc, JS_KEY),
+                            type_ann: None,
+                        })],
+                        body: Box::new(BlockStmtOrExpr::Expr(Box::new(entry))),
+                        is_async: false,
+                        is_generator: false,
+                        type_params: None,
+                        return_type: None,
+                    })],
+                );
+                let body = Self::method_call(
+                    loc,
+                    Expr::Ident(Self::special_ident("Object")),
+                    Self::special_ident("fromEntries"),
+                    vec![entries],
+                );
+                let pat = Pat::Ident(BindingIdent {
+                    id: Self::str_ident(loc, JS_MAPPED),
+                    type_ann: None,
+                });
+                let a = self.expr(sigma, loc, a)?;
+                Self::arrow_call(loc, Some(pat), body, a)
+            }
+            OptionalAccess(a, n) => {
+                // (__optional => __optional.n === undefined
+                //   ? {__enumT: "None", __enumV: undefined}
+                //   : {__enumT: "Some", __enumV: __optional.n})(a)
+                let field = Expr::Member(MemberExpr {
+                    span: loc.into(),
+                    obj: Box::new(Expr::Ident(Self::str_ident(loc, JS_OPTIONAL))),
+                    prop: MemberProp::Ident(Self::str_ident(loc, n)),
+                });
+                let body = Expr::Cond(CondExpr {
+                    span: loc.into(),
+                    test: Box::new(Expr::Bin(BinExpr {
+                        span: loc.into(),
+                        op: BinaryOp::EqEqEq,
+                        left: Box::new(field.clone()),
+                        right: Box::new(Expr::Ident(Self::undefined())),
+                    })),
+                    cons: Box::new(Self::variant(loc, "None", Expr::Ident(Self::undefined()))),
+                    alt: Box::new(Self::variant(loc, "Some", field)),
+                });
+                let pat = Pat::Ident(BindingIdent {
+                    id: Self::str_ident(loc, JS_OPTIONAL),
+                    type_ann: None,
+                });
+                let a = self.expr(sigma, loc, a)?;
+                Self::arrow_call(loc, Some(pat), body, a)
+            }
             Variant(f) => match f.as_ref() {
                 Fields(fields) => {
                     let (name, tm) = fields.iter().next().unwrap();
-                    Expr::Object(ObjectLit {
-                        span: loc.into(),
-                        props: vec![
-                            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                key: PropName::Ident(Self::str_ident(loc, JS_ENUM_TAG)),
-                                value: Box::new(Expr::Lit(Lit::Str(JsStr {
-                                    span: loc.into(),
-                                    value: name.as_str().into(),
-                                    raw: None,
-                                }))),
-                            }))),
-                            PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                                key: PropName::Ident(Self::str_ident(loc, JS_ENUM_VAL)),
-                                value: Box::new(self.expr(sigma, loc, &tm.clone())?),
-                            }))),
-                        ],
-                    })
+                    Self::variant(loc, name, self.expr(sigma, loc, &tm.clone())?)
                 }
                 _ => unreachable!(),
             },
@@ -561,7 +1086,7 @@ impl Ecma {
                         key: PropName::Ident(Self::str_ident(loc, n.as_str())),
                         value: Box::new(Expr::Arrow(ArrowExpr {
                             span: loc.into(),
-                            params: vec![Self::ident_pat(loc, v)],
+                            params: vec![Self::case_pat(loc, v, tm)],
                             body: Box::new(BlockStmtOrExpr::BlockStmt(self.block(
                                 sigma,
                                 loc,
@@ -626,20 +1151,61 @@ impl Ecma {
                     raw: None,
                 }))
             }
-            Find(_, _, f) => return Err(NonErasable(Ref(f.clone()), loc)),
+            Find(ts, i, f) => Expr::Member(MemberExpr {
+                span: loc.into(),
+                obj: Box::new(self.dict_of_scope(sigma, loc, ts, i)?),
+                prop: MemberProp::Ident(Self::ident(loc, f)),
+            }),
+            FindDict(ts, i) => self.dict_of_scope(sigma, loc, ts, i)?,
+            ImplementsDict(im, ds) => {
+                let dict = match self.emitted_ref(loc, im) {
+                    Some(dict) => dict,
+                    None => return Err(NonErasable(tm.clone(), loc)),
+                };
+                if ds.is_empty() {
+                    dict
+                } else {
+                    let mut args = Vec::default();
+                    for d in ds {
+                        args.push(ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(self.expr(sigma, loc, d)?),
+                        });
+                    }
+                    Expr::Call(CallExpr {
+                        span: loc.into(),
+                        callee: Callee::Expr(Box::new(dict)),
+                        args,
+                        type_args: None,
+                    })
+                }
+            }
 
             _ => unreachable!(),
         })
     }
 
-    fn imports(&self, imports: Vec<Import>) -> Result<Vec<ModuleItem>, Error> {
+    fn imports(&mut self, imports: Vec<Import>) -> Result<Vec<ModuleItem>, Error> {
         use ImportedDefs::*;
         let mut items = Vec::default();
         for i in imports {
             let mut specifiers = Vec::default();
             match i.defs {
                 Unqualified(defs) => {
+                    // Only the definitions existing at runtime are imported, not the types.
+                    let exports = self.exports.get(&i.module);
                     for (loc, d) in defs {
+                        if !exports.is_some_and(|e| e.contains(&d)) {
+                            continue;
+                        }
+                        // Already in scope for the references imported by `emitted_ref`.
+                        let imported = self
+                            .emitted
+                            .iter()
+                            .filter(|(v, m)| *m == &i.module && v.as_str() == d)
+                            .map(|(v, _)| v.clone())
+                            .collect::<Vec<_>>();
+                        self.imported.extend(imported);
                         specifiers.push(ImportSpecifier::Named(ImportNamedSpecifier {
                             span: loc.into(),
                             local: Self::str_ident(loc, d.as_str()),
@@ -676,6 +1242,44 @@ impl Ecma {
         Ok(items)
     }
 
+    /// Imports the dictionaries and default methods of other modules referenced by the
+    /// declarations.
+    fn dict_imports(&mut self) -> Vec<ModuleItem> {
+        let mut modules = Vec::<(ModuleID, Vec<ImportSpecifier>)>::default();
+        for (m, v) in std::mem::take(&mut self.dict_imports) {
+            let specifier = ImportSpecifier::Named(ImportNamedSpecifier {
+                span: DUMMY_SP,
+                local: Self::mangled_ident(Loc::default(), &v),
+                imported: None,
+                is_type_only: false,
+            });
+            match modules.iter_mut().find(|(n, _)| n == &m) {
+                Some((_, specifiers)) => specifiers.push(specifier),
+                None => modules.push((m, vec![specifier])),
+            }
+        }
+        modules
+            .into_iter()
+            .map(|(m, specifiers)| {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                    span: DUMMY_SP,
+                    specifiers,
+                    src: Box::new(JsStr {
+                        span: DUMMY_SP,
+                        value: m
+                            .to_generated_path()
+                            .join(self.filename())
+                            .to_string_lossy()
+                            .into(),
+                        raw: None,
+                    }),
+                    type_only: false,
+                    asserts: None,
+                }))
+            })
+            .collect()
+    }
+
     fn includes(&mut self, includes: &[PathBuf]) -> Result<Vec<ModuleItem>, Error> {
         let mut items = Vec::default();
         let mut props = Vec::default();
@@ -745,6 +1349,14 @@ impl Ecma {
                 }
                 Postulate => self.postulate_decl(&mut items, &def),
                 Const(_, f) => self.const_decl(&mut items, sigma, &def, f),
+                ImplementsFn(f) if !matches!(def.ret.as_ref(), Term::Univ) => {
+                    self.func_decl(&mut items, sigma, &def, f)
+                }
+                Implements(body) => self.implements_decl(&mut items, sigma, &def, body),
+                Interface { defaults, .. } => {
+                    self.defaults.extend(defaults.values().cloned());
+                    continue;
+                }
                 Undefined => unreachable!(),
                 _ => continue,
             } {
@@ -756,16 +1368,20 @@ impl Ecma {
     }
 
     fn try_export_decl(def: &Def<Term>, decl: Decl) -> ModuleItem {
-        if def.is_private() {
+        if !def.is_exported {
             ModuleItem::Stmt(Stmt::Decl(decl))
         } else {
-            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
-                span: def.loc.into(),
-                decl,
-            }))
+            Self::export_decl(def.loc, decl)
         }
     }
 
+    fn export_decl(loc: Loc, decl: Decl) -> ModuleItem {
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+            span: loc.into(),
+            decl,
+        }))
+    }
+
     fn func_decl(
         &mut self,
         items: &mut Vec<ModuleItem>,
@@ -773,17 +1389,206 @@ impl Ecma {
         def: &Def<Term>,
         body: &Term,
     ) -> Result<(), Error> {
-        items.push(Self::try_export_decl(
-            def,
-            Decl::Fn(FnDecl {
-                ident: Self::ident(def.loc, &def.name),
-                declare: false,
-                function: Box::new(self.func(sigma, def, body)?),
-            }),
-        ));
+        let decl = Decl::Fn(FnDecl {
+            ident: Self::mangled_ident(def.loc, &def.name),
+            declare: false,
+            function: Box::new(self.func(sigma, def, body)?),
+        });
+        // Default methods are referenced by the dictionaries of other modules.
+        items.push(match self.defaults.contains(&def.name) {
+            true => Self::export_decl(def.loc, decl),
+            false => Self::try_export_decl(def, decl),
+        });
+        self.emit(&def.name);
         Ok(())
     }
 
+    /// Emits the dictionary of an implementation, as a function of the dictionaries of its
+    /// predicates if any. Default methods are bound to the dictionary itself once it is built.
+    fn implements_decl(
+        &mut self,
+        items: &mut Vec<ModuleItem>,
+        sigma: &Sigma,
+        def: &Def<Term>,
+        body: &ImplementsBody<Term>,
+    ) -> Result<(), Error> {
+        use Body::*;
+
+        let loc = def.loc;
+        let n = Self::preds(&def.tele).len();
+        let defaults = match &sigma.get(&body.i.0).unwrap().body {
+            Interface { defaults, .. } => defaults,
+            _ => unreachable!(),
+        };
+        let mut fns = body.fns.iter().collect::<Vec<_>>();
+        fns.sort_by_key(|(i_fn, _)| i_fn.as_str());
+
+        let mut props = Vec::default();
+        let mut defaulted = Vec::default();
+        for (i_fn, im_fn) in fns {
+            if matches!(sigma.get(i_fn).unwrap().ret.as_ref(), Term::Univ) {
+                continue;
+            }
+            let f = match self.emitted_ref(loc, im_fn) {
+                Some(f) => f,
+                None => return Err(NonErasable(Term::Ref(im_fn.clone()), loc)),
+            };
+            if defaults.get(i_fn) == Some(im_fn) {
+                defaulted.push((i_fn, f));
+                continue;
+            }
+            let is_implements_fn = matches!(sigma.get(im_fn).unwrap().body, ImplementsFn(_));
+            let value = if n == 0 || !is_implements_fn {
+                f
+            } else {
+                Self::bind_dicts(
+                    loc,
+                    f,
+                    (0..n).map(|i| Expr::Ident(Self::dict_ident(loc, i))),
+                )
+            };
+            props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(Self::ident(loc, i_fn)),
+                value: Box::new(value),
+            }))));
+        }
+        self.preds = Self::preds(&def.tele);
+        let supers = body
+            .supers
+            .iter()
+            .map(|(s, d)| Ok((s, self.expr(sigma, loc, d)?)))
+            .collect::<Result<Vec<_>, Error>>();
+        self.preds.clear();
+        for (s, d) in supers? {
+            props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(Self::str_ident(loc, &s.super_dict())),
+                value: Box::new(d),
+            }))));
+        }
+        let dict = Expr::Object(ObjectLit {
+            span: loc.into(),
+            props,
+        });
+
+        let ident = Self::mangled_ident(loc, &def.name);
+        let self_ident = if n == 0 {
+            ident.clone()
+        } else {
+            Self::special_ident(JS_SELF_DICT)
+        };
+        let default_stmts = defaulted
+            .into_iter()
+            .map(|(i_fn, f)| {
+                Stmt::Expr(ExprStmt {
+                    span: loc.into(),
+                    expr: Box::new(Expr::Assign(AssignExpr {
+                        span: loc.into(),
+                        op: AssignOp::Assign,
+                        left: PatOrExpr::Expr(Box::new(Expr::Member(MemberExpr {
+                            span: loc.into(),
+                            obj: Box::new(Expr::Ident(self_ident.clone())),
+                            prop: MemberProp::Ident(Self::ident(loc, i_fn)),
+                        }))),
+                        right: Box::new(Self::bind_dicts(
+                            loc,
+                            f,
+                            [Expr::Ident(self_ident.clone())],
+                        )),
+                    })),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Dictionaries are always exported regardless of `export`: implementations are unnamed and
+        // visible wherever their interfaces are, so constraints resolved in other modules (e.g. by
+        // calling a constrained function) import the dictionaries by `dict_imports`.
+        if n == 0 {
+            items.push(Self::export_decl(
+                loc,
+                Self::const_decl_of(loc, ident, dict),
+            ));
+            items.extend(default_stmts.into_iter().map(ModuleItem::Stmt));
+        } else {
+            let mut stmts = Vec::default();
+            let ret = if default_stmts.is_empty() {
+                dict
+            } else {
+                stmts.push(Stmt::Decl(Self::const_decl_of(
+                    loc,
+                    self_ident.clone(),
+                    dict,
+                )));
+                stmts.extend(default_stmts);
+                Expr::Ident(self_ident)
+            };
+            stmts.push(Stmt::Return(ReturnStmt {
+                span: loc.into(),
+                arg: Some(Box::new(ret)),
+            }));
+            items.push(Self::export_decl(
+                loc,
+                Decl::Fn(FnDecl {
+                    ident,
+                    declare: false,
+                    function: Box::new(Function {
+                        params: Self::dict_params(loc, n),
+                        decorators: Default::default(),
+                        span: loc.into(),
+                        body: Some(BlockStmt {
+                            span: loc.into(),
+                            stmts,
+                        }),
+                        is_generator: false,
+                        is_async: false,
+                        type_params: None,
+                        return_type: None,
+                    }),
+                }),
+            ));
+        }
+        self.emit(&def.name);
+        Ok(())
+    }
+
+    /// Partially applies `f` to the given dictionaries.
+    fn bind_dicts(loc: Loc, f: Expr, dicts: impl IntoIterator<Item = Expr>) -> Expr {
+        let mut args = vec![ExprOrSpread {
+            spread: None,
+            expr: Box::new(Expr::Ident(Self::undefined())),
+        }];
+        args.extend(dicts.into_iter().map(|d| ExprOrSpread {
+            spread: None,
+            expr: Box::new(d),
+        }));
+        Expr::Call(CallExpr {
+            span: loc.into(),
+            callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+                span: loc.into(),
+                obj: Box::new(f),
+                prop: MemberProp::Ident(Self::special_ident("bind")),
+            }))),
+            args,
+            type_args: None,
+        })
+    }
+
+    fn const_decl_of(loc: Loc, ident: Ident, init: Expr) -> Decl {
+        Decl::Var(Box::new(VarDecl {
+            span: loc.into(),
+            kind: VarDeclKind::Const,
+            declare: false,
+            decls: vec![VarDeclarator {
+                span: loc.into(),
+                name: Pat::Ident(BindingIdent {
+                    id: ident,
+                    type_ann: None,
+                }),
+                init: Some(Box::new(init)),
+                definite: false,
+            }],
+        }))
+    }
+
     fn class_decls(
         &mut self,
         items: &mut Vec<ModuleItem>,
@@ -822,10 +1627,15 @@ impl Ecma {
         Ok(())
     }
 
-    fn postulate_decl(&self, items: &mut Vec<ModuleItem>, def: &Def<Term>) -> Result<(), Error> {
-        if def.is_private() {
+    fn postulate_decl(
+        &mut self,
+        items: &mut Vec<ModuleItem>,
+        def: &Def<Term>,
+    ) -> Result<(), Error> {
+        if !def.is_exported {
             return Ok(());
         }
+        self.emit(&def.name);
         items.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
             span: def.loc.into(),
             decl: Decl::Var(Box::new(VarDecl {
@@ -859,20 +1669,23 @@ impl Ecma {
                 span: def.loc.into(),
                 expr: Box::new(self.expr(sigma, def.loc, f)?),
             })),
-            _ => Self::try_export_decl(
-                def,
-                Decl::Var(Box::new(VarDecl {
-                    span: def.loc.into(),
-                    kind: VarDeclKind::Const,
-                    declare: false,
-                    decls: vec![VarDeclarator {
+            _ => {
+                self.emit(&def.name);
+                Self::try_export_decl(
+                    def,
+                    Decl::Var(Box::new(VarDecl {
                         span: def.loc.into(),
-                        name: Self::ident_pat(def.loc, &def.name),
-                        init: Some(Box::new(self.expr(sigma, def.loc, f)?)),
-                        definite: false,
-                    }],
-                })),
-            ),
+                        kind: VarDeclKind::Const,
+                        declare: false,
+                        decls: vec![VarDeclarator {
+                            span: def.loc.into(),
+                            name: Self::ident_pat(def.loc, &def.name),
+                            init: Some(Box::new(self.expr(sigma, def.loc, f)?)),
+                            definite: false,
+                        }],
+                    })),
+                )
+            }
         });
         Ok(())
     }
@@ -964,17 +1777,24 @@ impl Target for Ecma {
         &mut self,
         buf: &mut Vec<u8>,
         sigma: &Sigma,
+        module: &ModuleID,
         includes: &[PathBuf],
         file: ModuleFile,
     ) -> Result<(), Error> {
         self.vtbl.clear();
+        if &self.module != module {
+            self.module = module.clone();
+            self.imported.clear();
+        }
 
         let imports = self.imports(file.imports)?;
         let includes = self.includes(includes)?;
         let decls = self.decls(sigma, file.defs)?;
         let vtbl = self.vtbl_decl()?; // decls add new entries to vtbl
+        let dict_imports = self.dict_imports();
 
         let mut body = imports;
+        body.extend(dict_imports);
         body.extend(includes);
         body.extend(vtbl); // initialized earlier
         body.extend(decls);
@@ -20,6 +20,7 @@ pub trait Target {
         &mut self,
         buf: &mut Vec<u8>,
         sigma: &Sigma,
+        module: &ModuleID,
         includes: &[PathBuf],
         file: ModuleFile,
     ) -> Result<(), Error>;
@@ -55,7 +56,7 @@ impl Codegen {
 
         for f in files {
             let file = f.file.clone();
-            if let Err(e) = self.target.module(&mut buf, sigma, &includes, f) {
+            if let Err(e) = self.target.module(&mut buf, sigma, &module, &includes, f) {
                 return Err(print_err(e, &file, read_to_string(&file)?));
             }
         }
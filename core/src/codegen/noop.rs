@@ -25,6 +25,7 @@ impl Target for Noop {
         &mut self,
         _: &mut Vec<u8>,
         _: &Sigma,
+        _: &ModuleID,
         _: &[PathBuf],
         _: ModuleFile,
     ) -> Result<(), Error> {
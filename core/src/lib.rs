@@ -17,7 +17,7 @@ use crate::theory::conc::elab::Elaborator;
 use crate::theory::conc::load::{prelude_path, Import, Loaded, ModuleID};
 use crate::theory::conc::resolve::{NameMap, ResolvedVar, Resolver, VarKind};
 use crate::theory::conc::trans::Trans;
-use crate::theory::Loc;
+use crate::theory::{Loc, Var};
 
 pub mod codegen;
 #[cfg(test)]
@@ -35,6 +35,10 @@ pub enum Error {
     UnresolvedVar(Loc),
     #[error("duplicate name")]
     DuplicateName(Loc),
+    #[error("type alias \"{0}\" refers to itself")]
+    CyclicAlias(Var, Loc),
+    #[error("\"{0}\" is not exported")]
+    UnexportedVar(String, Loc),
 
     #[error("unresolved implicit parameter \"{0}\"")]
     UnresolvedImplicitParam(String, Loc),
@@ -53,20 +57,51 @@ pub enum Error {
     #[error("not exhaustive, got \"{0}\"")]
     NonExhaustive(Term, Loc),
     #[error("unresolved field \"{0}\" in \"{1}\"")]
-    UnresolvedField(String, Term, Loc),
+    UnresolvedField(String, Box<Term>, Loc),
     #[error("expected interface type, got \"{0}\"")]
     ExpectedInterface(Term, Loc),
     #[error("expected type alias, got \"{0}\"")]
     ExpectedAlias(Term, Loc),
-    #[error("unresolved implementation, got \"{0}\"")]
-    UnresolvedImplementation(Term, Loc),
+    #[error("unresolved implementation of \"{0}\" for \"{1}\"")]
+    UnresolvedImplementation(Var, String, Loc),
+    #[error("method \"{0}\" cannot be mapped over fields")]
+    UnmappableMethod(String, Loc),
+    #[error("interface \"{0}\" cannot be derived")]
+    UnderivableInterface(Var, Loc),
     #[error("expected constraint, got \"{0}\"")]
     ExpectedImplementsOf(Term, Loc),
+    #[error("missing method \"{0}\" of interface \"{1}\"")]
+    MissingMethod(Var, Var, Loc),
+    #[error("method \"{}\" expected type \"{}\", got \"{}\"", .0, .1.0, .1.1)]
+    MismatchedMethod(Var, Box<(Term, Term)>, Loc, #[source] Box<Error>),
+    #[error("implementation of interface \"{0}\" has {} missing or mismatched methods", .1.len())]
+    NonConforming(Var, Vec<Error>, Loc),
+    #[error("private member \"{0}\" is only accessible inside its class")]
+    PrivateMember(String, Loc),
+    #[error("missing implementation of superinterface \"{0}\" for \"{1}\"")]
+    MissingSuperImplementation(Var, String, Loc),
+    #[error("interface \"{0}\" expects {1} type argument(s), got {2}")]
+    MismatchedImplementors(Var, usize, usize, Loc),
+    #[error(
+        "constraint \"{0}\" required by implementation of \"{1}\" for \"{2}\" is not satisfied"
+    )]
+    UnsatisfiedConstraint(String, Var, String, Loc),
+    #[error("constraint \"{0}\" exceeds the maximum resolution depth")]
+    ConstraintDepthExceeded(String, Loc),
+    #[error("orphan implementation of \"{0}\", either the interface or the type must be local")]
+    OrphanImplementation(String, Loc),
+    #[error("overlapping implementations of \"{0}\" for \"{1}\"")]
+    OverlappingImplementations(Var, String, Loc, Option<Loc>),
+
+    #[error("static assertion failed, \"{}\" is not equal to \"{}\"{}", .0.0, .0.1, .1)]
+    FailedAssertion(Box<(Term, Term)>, String, Loc),
+    #[error("return type of recursive function \"{0}\" must be annotated")]
+    UnannotatedRecursion(Var, Loc),
 
     #[error("expected \"{0}\", found \"{1}\"")]
-    NonUnifiable(Term, Term, Loc),
+    NonUnifiable(Box<Term>, Box<Term>, Loc),
     #[error("field(s) \"{0}\" not contained in \"{1}\"")]
-    NonRowSat(Term, Term, Loc),
+    NonRowSat(Box<Term>, Box<Term>, Loc),
 
     #[error("unsolved meta \"{0}\"")]
     UnsolvedMeta(Term, Loc),
@@ -107,6 +142,8 @@ fn print_err<S: AsRef<str>>(e: Error, file: &Path, source: S) -> Error {
 
         UnresolvedVar(loc) => simple_message(&e, loc, RESOLVER_FAILED),
         DuplicateName(loc) => simple_message(&e, loc, RESOLVER_FAILED),
+        CyclicAlias(_, loc) => simple_message(&e, loc, RESOLVER_FAILED),
+        UnexportedVar(_, loc) => simple_message(&e, loc, RESOLVER_FAILED),
 
         UnresolvedImplicitParam(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
         ExpectedPi(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
@@ -119,8 +156,22 @@ fn print_err<S: AsRef<str>>(e: Error, file: &Path, source: S) -> Error {
         UnresolvedField(_, _, loc) => simple_message(&e, loc, CHECKER_FAILED),
         ExpectedInterface(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
         ExpectedAlias(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
-        UnresolvedImplementation(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        UnresolvedImplementation(_, _, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        UnmappableMethod(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        UnderivableInterface(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
         ExpectedImplementsOf(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        MissingMethod(_, _, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        MismatchedMethod(_, _, loc, _) => simple_message(&e, loc, CHECKER_FAILED),
+        NonConforming(_, _, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        PrivateMember(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        MissingSuperImplementation(_, _, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        MismatchedImplementors(_, _, _, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        UnsatisfiedConstraint(_, _, _, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        ConstraintDepthExceeded(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        OverlappingImplementations(_, _, loc, _) => simple_message(&e, loc, CHECKER_FAILED),
+        OrphanImplementation(_, loc) => simple_message(&e, loc, RESOLVER_FAILED),
+        FailedAssertion(_, _, loc) => simple_message(&e, loc, CHECKER_FAILED),
+        UnannotatedRecursion(_, loc) => simple_message(&e, loc, CHECKER_FAILED),
 
         NonUnifiable(_, _, loc) => simple_message(&e, loc, UNIFIER_FAILED),
         NonRowSat(_, _, loc) => simple_message(&e, loc, UNIFIER_FAILED),
@@ -142,6 +193,21 @@ fn print_err<S: AsRef<str>>(e: Error, file: &Path, source: S) -> Error {
                 .with_color(Color::Red),
         );
     }
+    if let OverlappingImplementations(_, _, _, Some(prev)) = &e {
+        b = b.with_label(
+            Label::new((file_str, prev.start..prev.end))
+                .with_message("previous implementation here")
+                .with_color(Color::Yellow),
+        );
+    }
+    if let MismatchedMethod(_, _, _, cause) = &e {
+        b = b.with_note(cause.to_string());
+    }
+    if let NonConforming(_, errors, _) = &e {
+        for e in errors {
+            b = b.with_note(e.to_string());
+        }
+    }
     b.finish()
         .print((file_str, Source::from(source.as_ref())))
         .unwrap();
@@ -203,6 +269,7 @@ impl Driver {
             self.elab.sigma.insert(def.name.clone(), def);
         }
         self.load(Loadable::ViaPath(prelude_path()), true)?;
+        self.elab.prelude(&self.builtins);
         self.load_module(ModuleID::default())
     }
 
@@ -282,7 +349,7 @@ impl Driver {
         imports.iter().fold(Ok(()), |r, i| {
             r.and_then(|_| self.load_module(i.module.clone()))
         })?;
-        let defs = Resolver::new(&self.builtins, &self.loaded)
+        let defs = Resolver::new(&self.builtins, &self.loaded, module.as_ref())
             .file(&mut imports, defs)
             .and_then(|d| self.elab.defs(d))?;
         for d in &defs {
@@ -292,9 +359,8 @@ impl Driver {
                     ResolvedVar(VarKind::InModule, d.name.clone()),
                 );
             }
-            match module {
-                Some(m) if !d.is_private() => self.loaded.insert(m, d)?,
-                _ => {}
+            if let Some(m) = module {
+                self.loaded.insert(m, d)?
             }
         }
         Ok((imports, defs))
@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_alias_self() {
+    match run_err(module_path!()) {
+        Error::CyclicAlias(a, Loc { line, .. }) => {
+            assert_eq!(a.as_str(), "List");
+            assert_eq!(line, 1);
+        }
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,27 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_class_conformance() {
+    match run_err(module_path!()) {
+        Error::NonConforming(i, errors, Loc { line, .. }) => {
+            assert_eq!(i.as_str(), "Show");
+            assert_eq!(line, 7);
+            let mut missing = errors
+                .iter()
+                .filter_map(|e| match e {
+                    Error::MissingMethod(m, _, _) => Some(m.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            missing.sort();
+            assert_eq!(missing, ["debug", "size"]);
+            assert!(matches!(
+                errors.last(),
+                Some(Error::MismatchedMethod(m, _, Loc { line: 9, .. }, _)) if m.as_str() == "show"
+            ));
+        }
+        _ => assert!(false),
+    }
+}
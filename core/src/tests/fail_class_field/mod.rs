@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_class_field() {
+    match run_err(module_path!()) {
+        Error::UnresolvedField(f, _, Loc { line, .. }) => {
+            assert_eq!(f, "total");
+            assert_eq!(line, 7);
+        }
+        _ => assert!(false),
+    }
+}
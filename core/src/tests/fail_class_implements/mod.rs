@@ -0,0 +1,15 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_class_implements() {
+    match run_err(module_path!()) {
+        Error::MissingMethod(m, i, Loc { line, .. }) => {
+            assert_eq!(m.as_str(), "debug");
+            assert_eq!(i.as_str(), "Show");
+            assert_eq!(line, 6);
+        }
+        _ => assert!(false),
+    }
+}
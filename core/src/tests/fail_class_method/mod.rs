@@ -0,0 +1,15 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_class_method() {
+    match run_err(module_path!()) {
+        Error::MismatchedMethod(m, _, Loc { line, .. }, cause) => {
+            assert_eq!(m.as_str(), "show");
+            assert_eq!(line, 7);
+            assert!(matches!(*cause, Error::NonUnifiable(_, _, _)));
+        }
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_class_private() {
+    match run_err(module_path!()) {
+        Error::PrivateMember(n, Loc { line, .. }) => {
+            assert_eq!(n, "balance");
+            assert_eq!(line, 13);
+        }
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,15 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_derive() {
+    match run_err(module_path!()) {
+        Error::UnresolvedImplementation(i, _, Loc { line, col, .. }) => {
+            assert_eq!(i.as_str(), "Show");
+            assert_eq!(line, 13);
+            assert_eq!(col, 22);
+        }
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_derive_interface() {
+    match run_err(module_path!()) {
+        Error::UnderivableInterface(i, Loc { line, .. }) => {
+            assert_eq!(i.as_str(), "Size");
+            assert_eq!(line, 5);
+        }
+        _ => assert!(false),
+    }
+}
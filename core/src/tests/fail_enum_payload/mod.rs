@@ -0,0 +1,11 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_enum_payload() {
+    match run_err(module_path!()) {
+        Error::NonUnifiable(_, _, Loc { line, .. }) => assert_eq!(line, 4),
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,11 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_implements_depth() {
+    match run_err(module_path!()) {
+        Error::ConstraintDepthExceeded(_, Loc { line, .. }) => assert_eq!(line, 14),
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_implements_orphan() {
+    match run_err(module_path!()) {
+        Error::OrphanImplementation(i, Loc { line, .. }) => {
+            assert_eq!(i, "Show");
+            assert_eq!(line, 3);
+        }
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,15 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_implements_overlap() {
+    match run_err(module_path!()) {
+        Error::OverlappingImplementations(i, _, Loc { line, .. }, Some(prev)) => {
+            assert_eq!(i.as_str(), "Show");
+            assert_eq!(line, 13);
+            assert_eq!(prev.line, 7);
+        }
+        _ => assert!(false),
+    }
+}
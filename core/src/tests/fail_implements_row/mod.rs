@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_implements_row() {
+    match run_err(module_path!()) {
+        Error::UnsatisfiedConstraint(_, i, _, Loc { line, .. }) => {
+            assert_eq!(i.as_str(), "Show");
+            assert_eq!(line, 18);
+        }
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,15 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_implements_where() {
+    match run_err(module_path!()) {
+        Error::UnsatisfiedConstraint(c, i, _, Loc { line, .. }) => {
+            assert_eq!(c, "Show<string>");
+            assert_eq!(i.as_str(), "Show");
+            assert_eq!(line, 14);
+        }
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_interface_extends() {
+    match run_err(module_path!()) {
+        Error::MissingSuperImplementation(i, _, Loc { line, .. }) => {
+            assert_eq!(i.as_str(), "Eq");
+            assert_eq!(line, 9);
+        }
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,16 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_interface_multi() {
+    match run_err(module_path!()) {
+        Error::MismatchedImplementors(i, expected, got, Loc { line, .. }) => {
+            assert_eq!(i.as_str(), "Convert");
+            assert_eq!(expected, 2);
+            assert_eq!(got, 1);
+            assert_eq!(line, 5);
+        }
+        _ => assert!(false),
+    }
+}
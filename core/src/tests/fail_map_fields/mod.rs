@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_map_fields() {
+    match run_err(module_path!()) {
+        Error::UnmappableMethod(f, Loc { line, .. }) => {
+            assert_eq!(f.as_str(), "pair");
+            assert_eq!(line, 12);
+        }
+        _ => assert!(false),
+    }
+}
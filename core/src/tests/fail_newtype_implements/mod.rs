@@ -0,0 +1,15 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_newtype_implements() {
+    match run_err(module_path!()) {
+        Error::UnresolvedImplementation(i, im, Loc { line, .. }) => {
+            assert_eq!(i.as_str(), "Show");
+            assert_eq!(im, "number");
+            assert_eq!(line, 5);
+        }
+        _ => assert!(false),
+    }
+}
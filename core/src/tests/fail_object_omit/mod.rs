@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_object_omit() {
+    match run_err(module_path!()) {
+        Error::UnresolvedField(f, _, Loc { line, .. }) => {
+            assert_eq!(f, "z");
+            assert_eq!(line, 4)
+        }
+        _ => assert!(false),
+    }
+}
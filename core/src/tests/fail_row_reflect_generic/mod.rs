@@ -0,0 +1,10 @@
+use crate::tests::run_err;
+use crate::Error;
+
+#[test]
+fn test_row_reflect_generic() {
+    match run_err(module_path!()) {
+        Error::FieldsUnknown(r, _) => assert_eq!(r.to_string(), "'R"),
+        _ => assert!(false),
+    }
+}
@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_static_assert_hole() {
+    match run_err(module_path!()) {
+        Error::FailedAssertion(_, diff, Loc { line, .. }) => {
+            assert_eq!(diff, ", holes are not allowed");
+            assert_eq!(line, 3);
+        }
+        _ => assert!(false),
+    }
+}
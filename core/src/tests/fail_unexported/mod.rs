@@ -0,0 +1,14 @@
+use crate::tests::run_err;
+use crate::theory::Loc;
+use crate::Error;
+
+#[test]
+fn test_unexported() {
+    match run_err(module_path!()) {
+        Error::UnexportedVar(n, Loc { line, .. }) => {
+            assert_eq!(n, "helper");
+            assert_eq!(line, 1);
+        }
+        _ => assert!(false),
+    }
+}
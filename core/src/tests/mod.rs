@@ -1,5 +1,9 @@
 use std::env;
-use std::path::Path;
+#[cfg(feature = "codegen-ecma")]
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "codegen-ecma")]
+use std::process::Command;
 #[cfg(feature = "codegen-ecma")]
 use std::rc::Rc;
 
@@ -22,28 +26,90 @@ use crate::codegen::noop::Noop;
 use crate::codegen::Target;
 use crate::{Driver, Error};
 
+mod fail_alias_cycle;
+mod fail_alias_self;
+mod fail_class_conformance;
+mod fail_class_field;
+mod fail_class_implements;
+mod fail_class_method;
+mod fail_class_private;
+mod fail_derive;
+mod fail_derive_interface;
+mod fail_enum_nominal;
+mod fail_enum_payload;
+mod fail_fn_infer;
+mod fail_fn_mutual;
 mod fail_hole;
+mod fail_implements_depth;
+mod fail_implements_orphan;
+mod fail_implements_overlap;
+mod fail_implements_row;
+mod fail_implements_where;
+mod fail_interface_extends;
+mod fail_interface_multi;
+mod fail_literal_type;
+mod fail_map_fields;
+mod fail_newtype;
+mod fail_newtype_implements;
+mod fail_object_let;
+mod fail_object_omit;
+mod fail_object_optional;
+mod fail_object_optional_param;
+mod fail_object_update;
 mod fail_parse;
 mod fail_reserved;
 mod fail_resolve;
+mod fail_row_reflect;
+mod fail_row_reflect_generic;
+mod fail_static_assert_hole;
+mod fail_static_assert_literal;
+mod fail_static_assert_union;
+mod fail_type_fn;
+mod fail_unexported;
 mod ok_alias;
 mod ok_bool;
 mod ok_builtin;
+mod ok_class_implements;
+mod ok_class_private;
+mod ok_class_static;
 mod ok_const;
+mod ok_dependent_if;
+mod ok_derive;
+mod ok_derive_shadow;
 mod ok_enum;
+mod ok_enum_nominal;
+mod ok_enum_payload;
 mod ok_enum_rowpoly;
 mod ok_fn;
+mod ok_fn_infer;
+mod ok_fn_mutual;
 mod ok_fn_recur;
+mod ok_implements_imported;
+mod ok_implements_row;
+mod ok_implements_where;
 mod ok_implicit_named;
 mod ok_implicit_unnamed;
 mod ok_interface;
+mod ok_interface_assoc;
+mod ok_interface_default;
+mod ok_interface_dict;
+mod ok_interface_extends;
+mod ok_interface_module;
+mod ok_interface_multi;
 mod ok_interface_stuck;
 mod ok_issue75;
 mod ok_issue78;
+mod ok_literal_type;
+mod ok_local_fn;
 mod ok_modsys;
+mod ok_newtype;
 mod ok_object;
 mod ok_object_assign;
+mod ok_object_let;
+mod ok_object_omit;
+mod ok_object_optional;
 mod ok_object_rowpoly;
+mod ok_object_update;
 mod ok_oop;
 mod ok_oop_generics;
 mod ok_oop_init;
@@ -52,6 +118,9 @@ mod ok_op;
 mod ok_postulate_fn;
 mod ok_postulate_type;
 mod ok_rev_app;
+mod ok_row_reflect;
+mod ok_type_fn;
+mod ok_type_forward;
 mod ok_typeclassopedia;
 mod ok_typeclassopedia_stuck;
 mod ok_unit;
@@ -66,7 +135,7 @@ fn run_target() -> Box<dyn Target> {
     Box::new(Ecma::default())
 }
 
-fn run_helper(mod_path: &str) -> Result<(), Error> {
+fn run_helper(mod_path: &str) -> Result<PathBuf, Error> {
     let target = run_target();
     let pkg = Path::new(env!("CARGO_MANIFEST_DIR"))
         .join("src")
@@ -74,7 +143,8 @@ fn run_helper(mod_path: &str) -> Result<(), Error> {
         .join(mod_path.to_string().split("::").last().unwrap());
     let mut driver = Driver::new(pkg, target);
     driver.run()?;
-    parse_outfiles(&driver.codegen.outdir)
+    parse_outfiles(&driver.codegen.outdir)?;
+    Ok(driver.codegen.outdir)
 }
 
 #[cfg(not(feature = "codegen-ecma"))]
@@ -125,7 +195,30 @@ fn parse_outfile(file: &Path) -> Result<(), Error> {
 }
 
 pub fn run_ok(mod_path: &str) {
-    run_helper(mod_path).unwrap()
+    run_helper(mod_path).unwrap();
+}
+
+/// Runs the emitted main module with Node.js, followed by the `checks` script which throws on
+/// failure. The checks are skipped if `node` is not on `PATH`, the code is still generated.
+#[cfg(feature = "codegen-ecma")]
+pub fn run_ok_js(mod_path: &str, checks: &str) {
+    let outdir = run_helper(mod_path).unwrap();
+    let script = outdir.join("checks.mjs");
+    let mut src = fs::read_to_string(outdir.join(OUT_FILE)).unwrap();
+    src.push_str(checks);
+    fs::write(&script, src).unwrap();
+    let out = match Command::new("node").arg(&script).output() {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("skipping JS checks of {mod_path}: `node` not found on PATH");
+            return;
+        }
+        r => r.expect("failed to run `node`"),
+    };
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
 }
 
 pub fn run_err(mod_path: &str) -> Error {
@@ -0,0 +1,6 @@
+use crate::tests::run_ok;
+
+#[test]
+fn test_class_implements() {
+    run_ok(module_path!())
+}
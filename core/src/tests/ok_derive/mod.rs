@@ -0,0 +1,17 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_derive() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (!f0()) throw new Error("f0");
+if (f1() !== "{x: 1, y: 2}") throw new Error("f1");
+if (f2() !== '{"At":{"x":1,"y":2}}') throw new Error("f2");
+if (f3() !== "{value: {x: 1, y: 2}}") throw new Error("f3");
+if (f4() !== "Counter {n: 42}") throw new Error("f4");
+if (JSON.parse(f6({name: 'a"b\\c', age: 1})).name !== 'a"b\\c') throw new Error("f6");
+if (f7() !== "Account {_balance: 42}") throw new Error("f7");
+"#,
+    )
+}
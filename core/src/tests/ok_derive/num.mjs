@@ -0,0 +1,7 @@
+export function numEq(a, b) {
+  return a === b;
+}
+
+export function numToStr(n) {
+  return n.toString();
+}
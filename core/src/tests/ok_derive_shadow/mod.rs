@@ -0,0 +1,11 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_derive_shadow() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (f0() !== "{x: 1, y: 2}") throw new Error("f0");
+"#,
+    )
+}
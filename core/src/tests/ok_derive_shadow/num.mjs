@@ -0,0 +1,3 @@
+export function numToStr(n) {
+  return n.toString();
+}
@@ -0,0 +1,13 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_enum_payload() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (f1() !== 10) throw new Error("f1");
+if (f2(f4()) !== 1) throw new Error("f2");
+if (f3() !== 2) throw new Error("f3");
+"#,
+    )
+}
@@ -0,0 +1,15 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_fn_mutual() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (f() !== false) throw new Error("f");
+if (g() !== true) throw new Error("g");
+if (counted() !== 42) throw new Error("counted");
+if (f3() !== true) throw new Error("f3");
+if (odd(7) !== true) throw new Error("odd");
+"#,
+    )
+}
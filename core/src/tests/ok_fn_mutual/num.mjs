@@ -0,0 +1,7 @@
+export function isZero(n) {
+  return n === 0;
+}
+
+export function dec(n) {
+  return n - 1;
+}
@@ -0,0 +1,16 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_implements_row() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (f0() !== "{n: 42, s: hello}") throw new Error("f0");
+if (f1() !== "{n: 42}") throw new Error("f1");
+if (f3() !== "{p: {x: 1, y: a}, q: 2}") throw new Error("f3");
+const d = {n: Show__for__number, s: Show__for__string};
+if (showAll(d, {n: 1, s: "x"}) !== "{n: 1, s: x}") throw new Error("showAll");
+if (Show__for__$_R$(d).show({n: 2, s: "y"}) !== "{n: 2, s: y}") throw new Error("show");
+"#,
+    )
+}
@@ -0,0 +1,10 @@
+export function numToStr(n) {
+  return n.toString();
+}
+
+export function render(o) {
+  const fields = Object.keys(o)
+    .sort()
+    .map((k) => `${k}: ${o[k]}`);
+  return `{${fields.join(", ")}}`;
+}
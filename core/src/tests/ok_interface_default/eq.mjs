@@ -0,0 +1 @@
+export function numEq(a, b) { return a === b }
@@ -0,0 +1,13 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_interface_default() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (g(Eq__for__number, 1, 2) !== true) throw new Error("neq(1, 2)");
+if (g(Eq__for__number, 1, 1) !== false) throw new Error("neq(1, 1)");
+if (g(Eq__for__Always, {n: 1}, {n: 2}) !== false) throw new Error("neq({n: 1}, {n: 2})");
+"#,
+    )
+}
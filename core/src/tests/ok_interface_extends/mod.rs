@@ -0,0 +1,14 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_interface_extends() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (le(Ord__for__number, 1, 2) !== true) throw new Error("le(1, 2)");
+if (le(Ord__for__number, 2, 2) !== true) throw new Error("le(2, 2)");
+if (le(Ord__for__number, 3, 2) !== false) throw new Error("le(3, 2)");
+if (le2(Ord__for__number, 2, 2) !== true) throw new Error("le2(2, 2)");
+"#,
+    )
+}
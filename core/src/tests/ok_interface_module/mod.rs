@@ -0,0 +1,17 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_interface_module() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (le(Ord__for__number, 1, 1) !== true) throw new Error("le(1, 1)");
+if (le(Ord__for__number, 2, 1) !== false) throw new Error("le(2, 1)");
+if (differ(Eq__for__string, "a", "b") !== true) throw new Error("differ(a, b)");
+if (sameNum(1, 1) !== true) throw new Error("sameNum(1, 1)");
+if (sameNum(1, 2) !== false) throw new Error("sameNum(1, 2)");
+if (sameOneTwo() !== false) throw new Error("sameOneTwo()");
+if (eqOneTwo() !== false) throw new Error("eqOneTwo()");
+"#,
+    )
+}
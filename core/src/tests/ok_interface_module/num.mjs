@@ -0,0 +1,2 @@
+export function numLt(a, b) { return a < b }
+export function strEq(a, b) { return a === b }
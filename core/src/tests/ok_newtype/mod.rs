@@ -0,0 +1,14 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_newtype() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (f0() !== "alice") throw new Error("f0");
+if (f2() !== "wrapped") throw new Error("f2");
+if (f3() !== "shown") throw new Error("f3");
+if (f4() !== "bob") throw new Error("f4");
+"#,
+    )
+}
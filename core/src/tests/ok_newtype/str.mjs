@@ -0,0 +1,3 @@
+export function strLen(s) {
+  return s.length;
+}
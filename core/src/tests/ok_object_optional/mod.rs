@@ -0,0 +1,6 @@
+use crate::tests::run_ok;
+
+#[test]
+fn test_object_optional() {
+    run_ok(module_path!())
+}
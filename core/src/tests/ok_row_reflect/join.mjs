@@ -0,0 +1,3 @@
+export function join(xs, sep) {
+  return xs.join(sep);
+}
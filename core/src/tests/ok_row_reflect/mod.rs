@@ -0,0 +1,14 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_row_reflect() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (f0() !== 42) throw new Error("f0");
+if (f1() !== "age, name") throw new Error("f1");
+if (f2() !== 0) throw new Error("f2");
+if (f3() !== 42) throw new Error("f3");
+"#,
+    )
+}
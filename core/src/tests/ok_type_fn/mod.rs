@@ -0,0 +1,6 @@
+use crate::tests::run_ok;
+
+#[test]
+fn test_type_fn() {
+    run_ok(module_path!())
+}
@@ -0,0 +1,12 @@
+use crate::tests::run_ok_js;
+
+#[test]
+fn test_type_forward() {
+    run_ok_js(
+        module_path!(),
+        r#"
+if (f0() !== 42) throw new Error("f0");
+if (f2() !== 1) throw new Error("f2");
+"#,
+    )
+}
@@ -33,12 +33,19 @@ fn tuple_args_body(mut args: Tele<Term>, mut body: Term) -> (Tele<Term>, Term) {
     });
     args.reverse();
 
+    let untupled_vars = args
+        .iter()
+        .map(|p| p.var.untupled_rhs())
+        .collect::<Vec<_>>();
     for i in 0..args.len() - 1 {
         let lhs = args.get(i + 1).unwrap().clone();
         let mut rhs = args.get(i).unwrap().clone();
-        let tm = Box::new(Term::Ref(rhs.var.clone()));
-        rhs.var = lhs.var.untupled_rhs();
-        body = Term::TupleLet(lhs, rhs, tm, Box::new(body));
+        rhs.var = untupled_vars.get(i + 1).unwrap().clone();
+        let tm = match untupled_vars.get(i + 2) {
+            Some(v) => v.clone(),
+            None => param_var.clone(),
+        };
+        body = Term::TupleLet(lhs, rhs, Box::new(Term::Ref(tm)), Box::new(body));
     }
 
     (
@@ -51,8 +58,20 @@ fn tuple_args_body(mut args: Tele<Term>, mut body: Term) -> (Tele<Term>, Term) {
     )
 }
 
-pub fn all_builtins() -> [Def<Term>; 3] {
-    [unionify(), number_add(), number_sub()]
+pub fn all_builtins() -> [Def<Term>; 11] {
+    [
+        unionify(),
+        string_add(),
+        quote("string#__quote__", Term::String),
+        quote("number#__quote__", Term::Number),
+        quote("boolean#__quote__", Term::Boolean),
+        number_add(),
+        number_sub(),
+        array(),
+        map_fields(),
+        partial(),
+        field_names(),
+    ]
 }
 
 fn unionify() -> Def<Term> {
@@ -70,12 +89,49 @@ fn unionify() -> Def<Term> {
     Def {
         loc: Default::default(),
         name: Var::new("unionify"),
+        is_exported: false,
         tele,
         ret: Box::new(Term::Enum(Box::new(Term::Ref(r)))),
         body: Body::Fn(body),
     }
 }
 
+fn string_add() -> Def<Term> {
+    let a = Var::new("a");
+    let b = Var::new("b");
+    let (tele, body) = tuple_args_body(
+        vec![
+            explicit_param(a.clone(), Term::String),
+            explicit_param(b.clone(), Term::String),
+        ],
+        Term::StrAdd(Box::new(Term::Ref(a)), Box::new(Term::Ref(b))),
+    );
+    Def {
+        loc: Default::default(),
+        name: Var::new("string#__add__"),
+        is_exported: false,
+        tele,
+        ret: Box::new(Term::String),
+        body: Body::Fn(body),
+    }
+}
+
+fn quote(name: &str, typ: Term) -> Def<Term> {
+    let a = Var::new("a");
+    let (tele, body) = tuple_args_body(
+        vec![explicit_param(a.clone(), typ)],
+        Term::StrQuote(Box::new(Term::Ref(a))),
+    );
+    Def {
+        loc: Default::default(),
+        name: Var::new(name),
+        is_exported: false,
+        tele,
+        ret: Box::new(Term::String),
+        body: Body::Fn(body),
+    }
+}
+
 fn number_add() -> Def<Term> {
     let a = Var::new("a");
     let b = Var::new("b");
@@ -89,6 +145,7 @@ fn number_add() -> Def<Term> {
     Def {
         loc: Default::default(),
         name: Var::new("number#__add__"),
+        is_exported: false,
         tele,
         ret: Box::new(Term::Number),
         body: Body::Fn(body),
@@ -108,8 +165,80 @@ fn number_sub() -> Def<Term> {
     Def {
         loc: Default::default(),
         name: Var::new("number#__sub__"),
+        is_exported: false,
         tele,
         ret: Box::new(Term::Number),
         body: Body::Fn(body),
     }
 }
+
+fn array() -> Def<Term> {
+    let t = Var::new("T");
+    Def {
+        loc: Default::default(),
+        name: Var::new("Array"),
+        is_exported: false,
+        tele: vec![implicit_param(t.clone(), Term::Univ)],
+        ret: Box::new(Term::Univ),
+        body: Body::Alias(Term::Array(Box::new(Term::Ref(t)))),
+    }
+}
+
+fn map_fields() -> Def<Term> {
+    let r = Var::new("'R");
+    let f = Var::new("F");
+    let kind = Term::Pi(
+        implicit_param(Var::unbound(), Term::Univ),
+        Box::new(Term::Univ),
+    );
+    Def {
+        loc: Default::default(),
+        name: Var::new("MapFields"),
+        is_exported: false,
+        tele: vec![
+            implicit_param(r.clone(), Term::Row),
+            implicit_param(f.clone(), kind),
+        ],
+        ret: Box::new(Term::Univ),
+        body: Body::Alias(Term::Object(Box::new(Term::MapFields(
+            Box::new(Term::Ref(r)),
+            Box::new(Term::Ref(f)),
+        )))),
+    }
+}
+
+fn partial() -> Def<Term> {
+    let r = Var::new("'R");
+    let t = Var::new("T");
+    let optional = Term::Lam(
+        implicit_param(t.clone(), Term::Univ),
+        Box::new(Term::Optional(Box::new(Term::Ref(t)))),
+    );
+    Def {
+        loc: Default::default(),
+        name: Var::new("Partial"),
+        is_exported: false,
+        tele: vec![implicit_param(r.clone(), Term::Row)],
+        ret: Box::new(Term::Univ),
+        body: Body::Alias(Term::Object(Box::new(Term::MapFields(
+            Box::new(Term::Ref(r)),
+            Box::new(optional),
+        )))),
+    }
+}
+
+fn field_names() -> Def<Term> {
+    let r = Var::new("'R");
+    let mut tele = vec![implicit_param(r.clone(), Term::Row)];
+    let (tupled_tele, body) =
+        tuple_args_body(Default::default(), Term::FieldNames(Box::new(Term::Ref(r))));
+    tele.extend(tupled_tele);
+    Def {
+        loc: Default::default(),
+        name: Var::new("fieldNames"),
+        is_exported: false,
+        tele,
+        ret: Box::new(Term::Array(Box::new(Term::String))),
+        body: Body::Fn(body),
+    }
+}
@@ -73,6 +73,8 @@ pub enum Term {
 
     String,
     Str(String),
+    StrAdd(Box<Self>, Box<Self>),
+    StrQuote(Box<Self>),
 
     Number,
     Num(f64),
@@ -82,9 +84,14 @@ pub enum Term {
     BigInt,
     Big(String),
 
+    Array(Box<Self>),
+    Arr(Vec<Self>),
+
     Row,
     Fields(FieldMap),
     Combine(Box<Self>, Box<Self>),
+    MapFields(Box<Self>, Box<Self>),
+    FieldNames(Box<Self>),
 
     RowOrd(Box<Self>, Dir, Box<Self>),
     RowSat,
@@ -97,20 +104,36 @@ pub enum Term {
     Concat(Box<Self>, Box<Self>),
     Access(Box<Self>, String),
     Downcast(Box<Self>, Box<Self>),
+    Restrict(Box<Self>, Vec<String>),
+    MapMethod(Box<Self>, Box<Self>, Var, Var),
+    Update(Box<Self>, Box<Self>),
+    Optional(Box<Self>),
+    Singleton(Box<Self>),
+    Union(Vec<Self>),
+    OptionalAccess(Box<Self>, String),
+    ObjectLet(
+        Vec<(String, Param<Self>)>,
+        Option<Param<Self>>,
+        Box<Self>,
+        Box<Self>,
+    ),
+    LocalFn(Var, Box<Self>, Box<Self>),
 
     Enum(Box<Self>),
     Variant(Box<Self>),
     Upcast(Box<Self>, Box<Self>),
     Switch(Box<Self>, CaseMap),
     Unionify(Box<Self>),
+    Nominal(Var, Vec<Self>),
 
     Vptr(Var, Vec<Self>),
     Vp(String, Vec<Self>),
     Lookup(Box<Self>),
 
-    Find(Box<Self>, Var, Var),
-    ImplementsOf(Box<Self>, Var),
-    ImplementsSat,
+    Find(Vec<Self>, Var, Var),
+    FindDict(Vec<Self>, Var),
+    ImplementsOf(Vec<Self>, Var),
+    ImplementsDict(Var, Vec<Self>),
 }
 
 impl Term {
@@ -169,12 +192,22 @@ impl Display for Term {
                 If(p, t, e) => format!("if {p} {{ {t} }} else {{ {e} }}"),
                 String => "string".to_string(),
                 Str(v) => format!("\"{v}\""),
+                StrAdd(a, b) => format!("{a} + {b}"),
+                StrQuote(a) => format!("JSON.stringify({a})"),
                 Number => "number".to_string(),
                 Num(v) => v.to_string(),
                 NumAdd(a, b) => format!("{a} + {b}"),
                 NumSub(a, b) => format!("{a} - {b}"),
                 BigInt => "bigint".to_string(),
                 Big(v) => v.clone(),
+                Array(t) => format!("Array<{t}>"),
+                Arr(xs) => format!(
+                    "[{}]",
+                    xs.iter()
+                        .map(|x| x.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
                 Row => "row".to_string(),
                 Fields(fields) => format!(
                     "({})",
@@ -185,6 +218,8 @@ impl Display for Term {
                         .join(", ")
                 ),
                 Combine(a, b) => format!("{a} + {b}"),
+                MapFields(r, f) => format!("MapFields<{r}, {f}>"),
+                FieldNames(r) => format!("fieldNames<{r}>()"),
                 RowOrd(a, d, b) => format!("{a} {d} {b}"),
                 RowSat => "sat".to_string(),
                 RowEq(a, b) => format!("{a} = {b}"),
@@ -194,6 +229,34 @@ impl Display for Term {
                 Concat(a, b) => format!("{a}...{b}"),
                 Access(a, n) => format!("{a}.{n}"),
                 Downcast(a, _) => format!("{{...{a}}}"),
+                Restrict(a, ns) => format!(
+                    "omit({a}, {})",
+                    ns.iter()
+                        .map(|n| format!("\"{n}\""))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                MapMethod(a, _, _, f) => format!("mapFields({a}, {f})"),
+                Update(a, f) => format!("{{...{a}, {f}}}"),
+                Optional(a) => format!("{a}?"),
+                Singleton(a) => a.to_string(),
+                Union(ts) => ts
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+                OptionalAccess(a, n) => format!("{a}?.{n}"),
+                ObjectLet(ps, rest, a, b) => {
+                    let mut s = ps
+                        .iter()
+                        .map(|(n, p)| format!("{n}: {p}"))
+                        .collect::<Vec<_>>();
+                    if let Some(r) = rest {
+                        s.push(format!("...{r}"));
+                    }
+                    format!("let {{{}}} = {a};\n\t{b}", s.join(", "))
+                }
+                LocalFn(v, a, b) => format!("function {v} = {a};\n\t{b}"),
                 Enum(r) => format!("[{r}]"),
                 Variant(r) => format!("[{r}]"),
                 Upcast(a, _) => format!("[...{a}]"),
@@ -207,6 +270,16 @@ impl Display for Term {
                     )
                 }
                 Unionify(a) => format!("unionify({a})"),
+                Nominal(r, ts) => match ts.is_empty() {
+                    true => r.to_string(),
+                    false => format!(
+                        "{r}<{}>",
+                        ts.iter()
+                            .map(|t| t.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                },
                 Vptr(r, ts) => format!(
                     "vptr@{r}<{}>",
                     ts.iter()
@@ -222,9 +295,34 @@ impl Display for Term {
                         .join(", ")
                 ),
                 Lookup(a) => format!("lookup({a})",),
-                Find(ty, i, f) => format!("{i}.{f}<{ty}>"),
-                ImplementsOf(t, i) => format!("{t} implementsOf {i}"),
-                ImplementsSat => "implementsSat".to_string(),
+                Find(ts, i, f) => format!(
+                    "{i}.{f}<{}>",
+                    ts.iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                FindDict(ts, i) => format!(
+                    "{i}<{}>",
+                    ts.iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                ImplementsOf(ts, i) => format!(
+                    "{} implementsOf {i}",
+                    ts.iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                ImplementsDict(im, ds) => format!(
+                    "implementsDict@{im}({})",
+                    ds.iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
             }
             .as_str(),
         )
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 
 use crate::theory::abs::data::Term::Vp;
@@ -13,6 +13,30 @@ pub type Sigma = HashMap<Var, Def<Term>>;
 pub type Gamma = HashMap<Var, Box<Term>>;
 pub type Rho = HashMap<Var, Box<Term>>;
 
+/// Finds the chain of superinterfaces through which the interface `from` extends `to`.
+pub fn super_path(sigma: &Sigma, from: &Var, to: &Var) -> Option<Vec<Var>> {
+    fn go(sigma: &Sigma, from: &Var, to: &Var, visited: &mut HashSet<Var>) -> Option<Vec<Var>> {
+        let supers = match &sigma.get(from)?.body {
+            Body::Interface { supers, .. } => supers,
+            _ => return None,
+        };
+        for s in supers {
+            if !visited.insert(s.clone()) {
+                continue;
+            }
+            if s == to {
+                return Some(vec![s.clone()]);
+            }
+            if let Some(mut path) = go(sigma, s, to, visited) {
+                path.insert(0, s.clone());
+                return Some(path);
+            }
+        }
+        None
+    }
+    go(sigma, from, to, &mut HashSet::from([from.clone()]))
+}
+
 pub fn gamma_to_tele(g: &Gamma) -> Tele<Term> {
     g.iter()
         .map(|(v, typ)| Param {
@@ -27,17 +51,12 @@ pub fn gamma_to_tele(g: &Gamma) -> Tele<Term> {
 pub struct Def<T: Syntax> {
     pub loc: Loc,
     pub name: Var,
+    pub is_exported: bool,
     pub tele: Tele<T>,
     pub ret: Box<T>,
     pub body: Body<T>,
 }
 
-impl<T: Syntax> Def<T> {
-    pub fn is_private(&self) -> bool {
-        matches!( self.name.as_str().chars().next(), Some(b) if b == '_')
-    }
-}
-
 impl Def<Expr> {
     pub fn to_type(&self) -> Expr {
         Expr::pi(&self.tele, *self.ret.clone())
@@ -51,7 +70,31 @@ impl Def<Term> {
             Fn(f) => self.to_lam_term(f.clone()),
             Postulate => Term::Extern(v),
             Alias(t) => self.to_lam_term(t.clone()),
+            NominalEnum(_) | Newtype(_) => self.to_lam_term(Term::Nominal(
+                v,
+                self.tele.iter().map(|p| Term::Ref(p.var.clone())).collect(),
+            )),
+            Coerce => {
+                let tupled = self.tele.last().unwrap();
+                let (a, rest) = match tupled.typ.as_ref() {
+                    Term::Sigma(a, rest) => (a.clone(), rest.clone()),
+                    _ => unreachable!(),
+                };
+                let rest = Param {
+                    var: Var::unbound(),
+                    info: Explicit,
+                    typ: rest,
+                };
+                let a_ref = Box::new(Term::Ref(a.var.clone()));
+                self.to_lam_term(Term::TupleLet(
+                    a,
+                    rest,
+                    Box::new(Term::Ref(tupled.var.clone())),
+                    a_ref,
+                ))
+            }
             Const(_, f) => self.to_lam_term(f.clone()),
+            StaticAssert(_, _) => Term::TT,
 
             Class(body) => self.to_lam_term(body.object.clone()),
             Ctor(f) => self.to_lam_term(f.clone()),
@@ -67,15 +110,18 @@ impl Def<Term> {
             )))),
 
             Interface { .. } => {
-                let r = Term::Ref(self.tele[0].var.clone());
-                self.to_lam_term(Term::ImplementsOf(Box::new(r), v))
+                let ts = self.tele.iter().map(|p| Term::Ref(p.var.clone())).collect();
+                self.to_lam_term(Term::ImplementsOf(ts, v))
             }
             Implements { .. } => unreachable!(),
             ImplementsFn(f) => self.to_lam_term(f.clone()),
-            Findable(i) => {
-                let r = Term::Ref(self.tele[0].var.clone());
-                let mut f = Term::Find(Box::new(r), i.clone(), v);
-                for p in self.tele.iter().skip(1) {
+            Findable(i, n) => {
+                let ts = self.tele[..*n]
+                    .iter()
+                    .map(|p| Term::Ref(p.var.clone()))
+                    .collect();
+                let mut f = Term::Find(ts, i.clone(), v);
+                for p in self.tele.iter().skip(*n) {
                     f = Term::App(
                         Box::new(f),
                         p.info.into(),
@@ -125,6 +171,25 @@ impl<T: Syntax> Display for Def<T> {
                     Param::tele_to_string(&self.tele),
                     self.ret,
                 ),
+                NominalEnum(t) => format!(
+                    "enum {} {}: {} = {t};",
+                    self.name,
+                    Param::tele_to_string(&self.tele),
+                    self.ret,
+                ),
+                Newtype(t) => format!(
+                    "newtype {} {}: {} = {t};",
+                    self.name,
+                    Param::tele_to_string(&self.tele),
+                    self.ret,
+                ),
+                Coerce => format!(
+                    "coerce {} {}: {};",
+                    self.name,
+                    Param::tele_to_string(&self.tele),
+                    self.ret,
+                ),
+                StaticAssert(a, b) => format!("static assert {a} = {b};"),
                 Const(anno, f) => {
                     if *anno {
                         format!("const {}: {} = {f};", self.name, self.ret)
@@ -172,13 +237,32 @@ impl<T: Syntax> Display for Def<T> {
                     Param::tele_to_string(&self.tele),
                     self.ret,
                 ),
-                Interface { fns, ims } => format!(
-                    "interface {} {}: {} {{\n{}\n{}}}",
+                Interface {
+                    fns,
+                    ims,
+                    defaults,
+                    supers,
+                } => format!(
+                    "interface {} {}: {}{} {{\n{}\n{}}}",
                     self.name,
                     Param::tele_to_string(&self.tele),
                     self.ret,
+                    match supers.is_empty() {
+                        true => String::default(),
+                        false => format!(
+                            " extends {}",
+                            supers
+                                .iter()
+                                .map(|s| s.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    },
                     fns.iter()
-                        .map(|f| format!("\t{f};\n"))
+                        .map(|f| match defaults.get(f) {
+                            Some(d) => format!("\t{f} = {d};\n"),
+                            None => format!("\t{f};\n"),
+                        })
                         .collect::<Vec<_>>()
                         .concat(),
                     ims.iter()
@@ -209,7 +293,7 @@ impl<T: Syntax> Display for Def<T> {
                         None => format!("meta {k}{} {tele}: {};", self.name, self.ret),
                     }
                 }
-                Findable(i) => format!(
+                Findable(i, _) => format!(
                     "findable {i}.{} {}: {};",
                     self.name,
                     Param::tele_to_string(&self.tele),
@@ -226,6 +310,10 @@ pub enum Body<T: Syntax> {
     Fn(T),
     Postulate,
     Alias(T),
+    NominalEnum(T),
+    Newtype(T),
+    Coerce,
+    StaticAssert(T, T),
     Const(bool, T),
 
     Class(Box<ClassBody<T>>),
@@ -236,10 +324,15 @@ pub enum Body<T: Syntax> {
     VtblType(T),
     VtblLookup,
 
-    Interface { fns: Vec<Var>, ims: Vec<Var> },
+    Interface {
+        fns: Vec<Var>,
+        ims: Vec<Var>,
+        defaults: HashMap<Var, Var>,
+        supers: Vec<Var>,
+    },
     Implements(Box<ImplementsBody<T>>),
     ImplementsFn(T),
-    Findable(Var),
+    Findable(Var, usize),
 
     Undefined,
     Meta(MetaKind, Option<T>),
@@ -249,6 +342,8 @@ pub enum Body<T: Syntax> {
 pub struct ClassBody<T: Syntax> {
     pub object: T,
     pub methods: Vec<(String, Var)>,
+    pub statics: Vec<Var>,
+    pub privates: Vec<String>,
     pub ctor: Var,
     pub vptr: Var,
     pub vptr_ctor: Var,
@@ -268,6 +363,7 @@ impl<T: Syntax> Display for ClassBody<T> {
     {};
     {};
     {};
+    {};
 }}",
             self.object,
             self.methods
@@ -275,6 +371,11 @@ impl<T: Syntax> Display for ClassBody<T> {
                 .map(|m| m.1.to_string())
                 .collect::<Vec<_>>()
                 .join(";\n    "),
+            self.statics
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(";\n    "),
             self.ctor,
             self.vptr,
             self.vptr_ctor,
@@ -286,26 +387,31 @@ impl<T: Syntax> Display for ClassBody<T> {
 
 #[derive(Clone, Debug)]
 pub struct ImplementsBody<T: Syntax> {
-    pub i: (Var, Box<T>),
+    pub i: (Var, Vec<T>),
     pub fns: HashMap<Var, Var>,
+    pub supers: Vec<(Var, T)>,
 }
 
 impl ImplementsBody<Term> {
-    pub fn implementor_type(&self, sigma: &Sigma) -> Result<Term, Error> {
+    pub fn implementor_types(&self, sigma: &Sigma) -> Result<Vec<Term>, Error> {
         use Body::*;
         use Error::*;
         use Term::*;
-        Ok(match self.i.1.as_ref() {
-            Ref(im) => {
-                let im = im.clone();
-                let def = sigma.get(&im).unwrap();
-                if !matches!(def.body, Alias(_)) {
-                    return Err(ExpectedAlias(Ref(im), def.loc));
+        let mut ret = Vec::default();
+        for ty in &self.i.1 {
+            ret.push(match ty {
+                Ref(im) => {
+                    let im = im.clone();
+                    let def = sigma.get(&im).unwrap();
+                    if !matches!(def.body, Alias(_) | Class(_) | Newtype(_)) {
+                        return Err(ExpectedAlias(Ref(im), def.loc));
+                    }
+                    def.to_term(im.clone())
                 }
-                def.to_term(im.clone())
-            }
-            tm => tm.clone(),
-        })
+                tm => tm.clone(),
+            });
+        }
+        Ok(ret)
     }
 }
 
@@ -315,7 +421,12 @@ impl<T: Syntax> Display for ImplementsBody<T> {
             f,
             "implements {} for {} {{\n{}}}",
             self.i.0,
-            self.i.1,
+            self.i
+                .1
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
             self.fns
                 .iter()
                 .map(|(i, im)| format!("\t{i}; {im};\n"))
@@ -1,18 +1,28 @@
+use std::collections::HashSet;
+
+use crate::theory::abs::data::MetaKind::InsertedMeta;
 use crate::theory::abs::data::Term::{App, Lam};
-use crate::theory::abs::data::{CaseMap, Dir, Term};
-use crate::theory::abs::def::{Body, Rho, Sigma};
+use crate::theory::abs::data::{CaseMap, Dir, FieldMap, Term};
+use crate::theory::abs::def::{super_path, Body, Def, Rho, Sigma};
 use crate::theory::abs::rename::rename;
 use crate::theory::abs::unify::Unifier;
 use crate::theory::conc::data::ArgInfo;
-use crate::theory::conc::data::ArgInfo::UnnamedExplicit;
-use crate::theory::{Loc, Param, Var};
+use crate::theory::conc::data::ArgInfo::{UnnamedExplicit, UnnamedImplicit};
+use crate::theory::{Loc, Param, Tele, Var};
 use crate::Error;
-use crate::Error::{UnresolvedField, UnresolvedImplementation};
+use crate::Error::{
+    ConstraintDepthExceeded, UnresolvedField, UnresolvedImplementation, UnsatisfiedConstraint,
+};
+
+const MAX_CONSTRAINT_DEPTH: usize = 32;
 
 pub struct Normalizer<'a> {
     sigma: &'a mut Sigma,
     rho: Rho,
     loc: Loc,
+    depth: usize,
+    assumptions: Option<Vec<(Vec<Term>, Var, Term)>>,
+    unfolding: HashSet<Var>,
 }
 
 impl<'a> Normalizer<'a> {
@@ -21,6 +31,9 @@ impl<'a> Normalizer<'a> {
             sigma,
             rho: Default::default(),
             loc,
+            depth: Default::default(),
+            assumptions: Default::default(),
+            unfolding: Default::default(),
         }
     }
 
@@ -58,11 +71,26 @@ impl<'a> Normalizer<'a> {
                             self.term(ret)?
                         }
                         None => {
-                            if let Some(tm) = Self::auto_implicit(&def.ret) {
-                                def.body = Meta(k, Some(tm.clone()));
-                                tm
-                            } else {
-                                MetaRef(k, x.clone(), sp)
+                            let tm = match def.ret.as_ref() {
+                                // Constraints on unsolved types are resolved once they are solved.
+                                ImplementsOf(ts, _)
+                                    if ts.iter().any(|t| matches!(t, MetaRef(_, _, _))) =>
+                                {
+                                    None
+                                }
+                                ImplementsOf(ts, i) => Some(self.evidence(&def.tele, ts, i)?),
+                                ty => Self::auto_implicit(ty),
+                            };
+                            match tm {
+                                Some(tm) => {
+                                    def.body = Meta(k, Some(tm.clone()));
+                                    let mut ret = rename(Term::lam(&def.tele, tm));
+                                    for (_, x) in sp {
+                                        ret = App(Box::new(ret), UnnamedExplicit, Box::new(x))
+                                    }
+                                    self.term(ret)?
+                                }
+                                None => MetaRef(k, x.clone(), sp),
                             }
                         }
                     },
@@ -84,6 +112,15 @@ impl<'a> Normalizer<'a> {
             Pi(p, b) => Pi(self.param(p)?, self.term_box(b)?),
             Lam(p, b) => Lam(self.param(p)?, self.term_box(b)?),
             App(f, ai, x) => {
+                if self.is_folded(&f) {
+                    // Keep recursive references folded until the call is saturated, and those
+                    // reached again while unfolding, so that calls with stuck arguments terminate.
+                    if ai != UnnamedExplicit || self.is_unfolding(&f) {
+                        let f = self.folded_spine(*f)?;
+                        return Ok(App(Box::new(f), ai, self.term_box(x)?));
+                    }
+                    return self.unfold(App(f, ai, x));
+                }
                 let f = self.term_box(f)?;
                 let x = self.term_box(x)?;
                 if let Lam(p, b) = *f {
@@ -123,6 +160,15 @@ impl<'a> Normalizer<'a> {
                     _ => If(p, t, e),
                 }
             }
+            StrAdd(a, b) => {
+                let a = self.term_box(a)?;
+                let b = self.term_box(b)?;
+                match (*a, *b) {
+                    (Str(a), Str(b)) => Str(a + b.as_str()),
+                    (a, b) => StrAdd(Box::new(a), Box::new(b)),
+                }
+            }
+            StrQuote(a) => StrQuote(self.term_box(a)?),
             NumAdd(a, b) => {
                 let a = self.term_box(a)?;
                 let b = self.term_box(b)?;
@@ -149,6 +195,37 @@ impl<'a> Normalizer<'a> {
                 }
                 Fields(fields)
             }
+            Array(t) => Array(self.term_box(t)?),
+            Arr(xs) => Arr(xs
+                .into_iter()
+                .map(|x| self.term(x))
+                .collect::<Result<Vec<_>, _>>()?),
+            MapFields(r, f) => {
+                let r = self.term_box(r)?;
+                let f = self.term_box(f)?;
+                match *r {
+                    Fields(fields) => {
+                        let mut mapped = FieldMap::default();
+                        for (n, t) in fields {
+                            let t = self.apply(*f.clone(), UnnamedImplicit, &[t])?;
+                            mapped.insert(n, t);
+                        }
+                        Fields(mapped)
+                    }
+                    r => MapFields(Box::new(r), f),
+                }
+            }
+            FieldNames(r) => {
+                let r = self.term_box(r)?;
+                match *r {
+                    Fields(fields) => {
+                        let mut names = fields.into_keys().collect::<Vec<_>>();
+                        names.sort();
+                        Arr(names.into_iter().map(Str).collect())
+                    }
+                    r => FieldNames(Box::new(r)),
+                }
+            }
             Combine(a, b) => {
                 let mut a = self.term_box(a)?;
                 let b = self.term_box(b)?;
@@ -216,10 +293,7 @@ impl<'a> Normalizer<'a> {
                             // TODO: eliminate clone
                             *y = y
                                 .keys()
-                                .map(|n| {
-                                    let tm = x.get(n).unwrap().clone();
-                                    (n.clone(), tm)
-                                })
+                                .filter_map(|n| x.get(n).map(|tm| (n.clone(), tm.clone())))
                                 .collect();
                             Obj(f)
                         }
@@ -228,6 +302,92 @@ impl<'a> Normalizer<'a> {
                     _ => Downcast(a, f),
                 }
             }
+            Restrict(a, ns) => {
+                let mut a = self.term_box(a)?;
+                match a.as_mut() {
+                    Obj(o) => match o.as_mut() {
+                        Fields(x) => {
+                            for n in &ns {
+                                x.remove(n);
+                            }
+                            *a
+                        }
+                        _ => Restrict(a, ns),
+                    },
+                    _ => Restrict(a, ns),
+                }
+            }
+            MapMethod(a, r, i, f) => {
+                let a = self.term_box(a)?;
+                let r = self.term_box(r)?;
+                match *r {
+                    Fields(fields) => {
+                        let mut mapped = FieldMap::default();
+                        for (n, t) in fields {
+                            let x = Tuple(Box::new(Access(a.clone(), n.clone())), Box::new(TT));
+                            let m = Find(vec![t], i.clone(), f.clone());
+                            let tm = App(Box::new(m), UnnamedExplicit, Box::new(x));
+                            mapped.insert(n, self.term(tm)?);
+                        }
+                        Obj(Box::new(Fields(mapped)))
+                    }
+                    r => MapMethod(a, Box::new(r), i, f),
+                }
+            }
+            ObjectLet(ps, rest, a, b) => {
+                let a = self.term_box(a)?;
+                match a.as_ref() {
+                    Obj(o) => match o.as_ref() {
+                        Fields(x) => {
+                            let mut x = x.clone();
+                            for (n, p) in ps {
+                                self.rho.insert(p.var, Box::new(x.remove(&n).unwrap()));
+                            }
+                            if let Some(p) = rest {
+                                self.rho.insert(p.var, Box::new(Obj(Box::new(Fields(x)))));
+                            }
+                            self.term(*b)?
+                        }
+                        _ => ObjectLet(ps, rest, a, self.term_box(b)?),
+                    },
+                    _ => ObjectLet(ps, rest, a, self.term_box(b)?),
+                }
+            }
+            LocalFn(v, a, b) => LocalFn(v, self.term_box(a)?, self.term_box(b)?),
+            Optional(a) => Optional(self.term_box(a)?),
+            Singleton(a) => Singleton(self.term_box(a)?),
+            Union(ts) => Union(
+                ts.into_iter()
+                    .map(|t| self.term(t))
+                    .collect::<Result<_, _>>()?,
+            ),
+            OptionalAccess(a, n) => {
+                let a = self.term_box(a)?;
+                match a.as_ref() {
+                    Obj(o) => match o.as_ref() {
+                        Fields(x) if !x.contains_key(&n) => {
+                            Variant(Box::new(Fields(FieldMap::from([("None".to_string(), TT)]))))
+                        }
+                        _ => OptionalAccess(a, n),
+                    },
+                    _ => OptionalAccess(a, n),
+                }
+            }
+            Update(a, f) => {
+                let mut a = self.term_box(a)?;
+                let f = self.term_box(f)?;
+                match (a.as_mut(), f.as_ref()) {
+                    (Obj(x), Fields(y)) => match x.as_mut() {
+                        Fields(x) => {
+                            // TODO: eliminate clone
+                            x.extend(y.iter().map(|(n, tm)| (n.clone(), tm.clone())));
+                            *a
+                        }
+                        _ => Update(a, f),
+                    },
+                    _ => Update(a, f),
+                }
+            }
             Enum(r) => Enum(self.term_box(r)?),
             Variant(r) => Variant(self.term_box(r)?),
             Upcast(a, f) => {
@@ -237,7 +397,7 @@ impl<'a> Normalizer<'a> {
                         (Fields(x), Fields(y)) => {
                             let name = x.iter().next().unwrap().0;
                             if !y.contains_key(name) {
-                                return Err(UnresolvedField(name.clone(), *f, self.loc));
+                                return Err(UnresolvedField(name.clone(), f, self.loc));
                             }
                             *a
                         }
@@ -263,6 +423,13 @@ impl<'a> Normalizer<'a> {
                 }
             }
             Unionify(a) => Unionify(self.term_box(a)?),
+            Nominal(r, ts) => {
+                let types = ts
+                    .into_iter()
+                    .map(|t| self.term(t))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Nominal(r, types)
+            }
             Vptr(r, ts) => {
                 let types = ts
                     .into_iter()
@@ -278,24 +445,101 @@ impl<'a> Normalizer<'a> {
                 Vp(r, types)
             }
             Lookup(a) => Lookup(self.term_box(a)?),
-            ImplementsOf(a, i) => {
-                let a = self.term_box(a)?;
-                if !matches!(*a, Ref(_) | MetaRef(_, _, _)) {
-                    self.check_constraint(&a, &i)?;
+            ImplementsOf(ts, i) => {
+                let ts = ts
+                    .into_iter()
+                    .map(|t| self.term(t))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !Self::is_stuck(&ts) {
+                    self.check_constraint(&ts, &i)?;
+                }
+                ImplementsOf(ts, i)
+            }
+            Find(ts, i, f) => {
+                let ts = ts
+                    .into_iter()
+                    .map(|t| self.term(t))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !Self::is_stuck(&ts) {
+                    return self.find_implementation(ts, i, f);
                 }
-                ImplementsOf(a, i)
+                Find(ts, i, f)
             }
-            Find(ty, i, f) => {
-                let ty = self.term_box(ty)?;
-                if !matches!(*ty, Ref(_) | MetaRef(_, _, _)) {
-                    return self.find_implementation(*ty, i, f);
+            FindDict(ts, i) => {
+                let ts = ts
+                    .into_iter()
+                    .map(|t| self.term(t))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !Self::is_stuck(&ts) {
+                    return self.check_constraint(&ts, &i);
                 }
-                Find(ty, i, f)
+                FindDict(ts, i)
             }
+            ImplementsDict(im, ds) => {
+                let ds = ds
+                    .into_iter()
+                    .map(|t| self.term(t))
+                    .collect::<Result<Vec<_>, _>>()?;
+                ImplementsDict(im, ds)
+            }
+            tm => tm,
+        })
+    }
+
+    fn is_folded(&self, tm: &Term) -> bool {
+        match tm {
+            Term::Undef(v) => !matches!(
+                self.sigma.get(v).unwrap().body,
+                Body::Undefined | Body::NominalEnum(_)
+            ),
+            App(f, ai, _) if ai != &UnnamedExplicit => self.is_folded(f),
+            _ => false,
+        }
+    }
+
+    fn is_unfolding(&self, tm: &Term) -> bool {
+        match tm {
+            Term::Undef(v) => self.unfolding.contains(v),
+            App(f, _, _) => self.is_unfolding(f),
+            _ => false,
+        }
+    }
+
+    fn folded_spine(&mut self, tm: Term) -> Result<Term, Error> {
+        Ok(match tm {
+            App(f, ai, x) => App(Box::new(self.folded_spine(*f)?), ai, self.term_box(x)?),
             tm => tm,
         })
     }
 
+    /// Unfolds a saturated recursive call, binding all the arguments before normalizing the body.
+    fn unfold(&mut self, mut tm: Term) -> Result<Term, Error> {
+        let mut spine = Vec::default();
+        while let App(f, ai, x) = tm {
+            spine.push((ai, self.term(*x)?));
+            tm = *f;
+        }
+        let (v, mut ret) = match tm {
+            Term::Undef(v) => (v.clone(), self.sigma.get(&v).unwrap().to_term(v)),
+            _ => unreachable!(),
+        };
+        for (ai, x) in spine.into_iter().rev() {
+            ret = match ret {
+                Lam(p, b) => {
+                    self.rho.insert(p.var, Box::new(x));
+                    *b
+                }
+                f => App(Box::new(f), ai, Box::new(x)),
+            };
+        }
+        let is_outermost = self.unfolding.insert(v.clone());
+        let ret = self.term(ret);
+        if is_outermost {
+            self.unfolding.remove(&v);
+        }
+        ret
+    }
+
     pub fn with(&mut self, rho: &[(&Var, &Term)], tm: Term) -> Result<Term, Error> {
         for &(x, v) in rho {
             self.rho.insert(x.clone(), Box::new(v.clone()));
@@ -337,58 +581,354 @@ impl<'a> Normalizer<'a> {
         match tm {
             RowEq(_, _) => Some(RowRefl),
             RowOrd(_, _, _) => Some(RowSat),
-            ImplementsOf(_, _) => Some(ImplementsSat),
             _ => None,
         }
     }
 
-    fn check_constraint(&mut self, x: &Term, i: &Var) -> Result<(), Error> {
-        use Body::*;
+    fn is_stuck(ts: &[Term]) -> bool {
+        ts.iter()
+            .any(|t| matches!(t, Term::Ref(_) | Term::MetaRef(_, _, _)))
+    }
+
+    /// Unifies the terms pairwise, restoring the metas solved on the way if any pair fails, so
+    /// that a rejected candidate leaves no solutions behind.
+    fn unify_all(&mut self, xs: &[Term], ys: &[Term]) -> bool {
+        let unsolved = self
+            .sigma
+            .iter()
+            .filter(|(_, d)| matches!(d.body, Body::Meta(_, None)))
+            .map(|(v, _)| v.clone())
+            .collect::<Vec<_>>();
+        let ok = xs
+            .iter()
+            .zip(ys)
+            .all(|(x, y)| Unifier::new(self.sigma, self.loc).unify(x, y).is_ok());
+        if !ok {
+            for v in unsolved {
+                if let Body::Meta(_, s) = &mut self.sigma.get_mut(&v).unwrap().body {
+                    *s = None;
+                }
+            }
+        }
+        ok
+    }
+
+    /// Checks that `xs` implement `i`, returning the dictionary of the found implementation.
+    pub fn check_constraint(&mut self, xs: &[Term], i: &Var) -> Result<Term, Error> {
+        if let [Term::Fields(fields)] = xs {
+            return self.check_fields(fields, i);
+        }
+
+        self.check_supers(xs, i, &mut HashSet::from([i.clone()]))?;
+        self.find_dict(xs, i)
+    }
+
+    /// Checks that `xs` implement every superinterface of `i`, visiting each interface once so that
+    /// cyclic `extends` terminates.
+    fn check_supers(
+        &mut self,
+        xs: &[Term],
+        i: &Var,
+        visited: &mut HashSet<Var>,
+    ) -> Result<(), Error> {
+        let supers = match &self.sigma.get(i).unwrap().body {
+            Body::Interface { supers, .. } => supers.clone(),
+            _ => unreachable!(),
+        };
+        for s in supers {
+            if visited.insert(s.clone()) {
+                self.find_dict(xs, &s)?;
+                self.check_supers(xs, &s, visited)?;
+            }
+        }
+        Ok(())
+    }
 
+    fn find_dict(&mut self, xs: &[Term], i: &Var) -> Result<Term, Error> {
         let ims = match &self.sigma.get(i).unwrap().body {
-            Interface { ims, .. } => ims.clone(),
+            Body::Interface { ims, .. } => ims.clone(),
             _ => unreachable!(),
         };
         for im in ims {
-            let y = match &self.sigma.get(&im).unwrap().body {
-                Implements(body) => body.implementor_type(self.sigma)?,
-                _ => unreachable!(),
+            if let Some(args) = self.implements_args(i, &im, xs)? {
+                return Ok(self.implements_dict(im, args));
+            }
+        }
+        Err(UnresolvedImplementation(
+            i.clone(),
+            Self::implementors(xs),
+            self.loc,
+        ))
+    }
+
+    /// Builds the dictionary of the implementation `im` from its solved implicit arguments.
+    fn implements_dict(&self, im: Var, args: Vec<Term>) -> Term {
+        let ds = self
+            .sigma
+            .get(&im)
+            .unwrap()
+            .tele
+            .iter()
+            .zip(args)
+            .filter(|(p, _)| matches!(p.typ.as_ref(), Term::ImplementsOf(_, _)))
+            .map(|(_, d)| d)
+            .collect();
+        Term::ImplementsDict(im, ds)
+    }
+
+    /// Checks that every field type of a row implements `i`, returning an object of the field
+    /// dictionaries.
+    fn check_fields(&mut self, fields: &FieldMap, i: &Var) -> Result<Term, Error> {
+        use Term::*;
+
+        let mut ds = FieldMap::default();
+        for (n, t) in fields {
+            let ts = [t.clone()];
+            let d = if Self::is_stuck(&ts) {
+                self.assumption(&ts, i)?
+            } else {
+                self.check_constraint(&ts, i)?
+            };
+            ds.insert(n.clone(), d);
+        }
+        Ok(Obj(Box::new(Fields(ds))))
+    }
+
+    /// Matches the implementation `im` against `ts` and discharges its predicates, returning the
+    /// arguments for its implicit parameters.
+    fn implements_args(
+        &mut self,
+        i: &Var,
+        im: &Var,
+        ts: &[Term],
+    ) -> Result<Option<Vec<Term>>, Error> {
+        let (tele, im_tys) = self.implementor_of(im)?;
+        let metas = self.implements_metas(&tele);
+        let ret = self.instantiate_args(i, ts, tele, im_tys, &metas);
+        self.remove_metas(metas);
+        ret
+    }
+
+    fn instantiate_args(
+        &mut self,
+        i: &Var,
+        ts: &[Term],
+        tele: Tele<Term>,
+        im_tys: Vec<Term>,
+        metas: &[(Var, Term)],
+    ) -> Result<Option<Vec<Term>>, Error> {
+        use Term::*;
+
+        let ys = self.instantiate(metas, im_tys)?;
+        if !self.unify_all(&ys, ts) {
+            return Ok(None);
+        }
+
+        let rho = metas.iter().map(|(v, m)| (v, m)).collect::<Vec<_>>();
+
+        let mut args = Vec::default();
+        for p in tele {
+            args.push(match *p.typ {
+                ImplementsOf(pts, pi) => {
+                    let mut xs = Vec::default();
+                    for t in pts {
+                        xs.push(Normalizer::new(self.sigma, self.loc).with(&rho, t)?);
+                    }
+                    self.discharge(i, ts, &xs, &pi)?
+                }
+                typ => match Self::auto_implicit(&typ) {
+                    Some(sat) => sat,
+                    None => Normalizer::new(self.sigma, self.loc).with(&rho, Ref(p.var))?,
+                },
+            });
+        }
+        Ok(Some(args))
+    }
+
+    /// Checks whether some instantiation of the implementation `im` also matches the implementor
+    /// types `tys` under the implicit parameters `tele`, ignoring predicates.
+    pub fn is_overlapping(
+        &mut self,
+        tele: &Tele<Term>,
+        tys: Vec<Term>,
+        im: &Var,
+    ) -> Result<bool, Error> {
+        let metas = self.implements_metas(tele);
+        let xs = self.instantiate(&metas, tys)?;
+        let (im_tele, im_tys) = self.implementor_of(im)?;
+        let metas = self.implements_metas(&im_tele);
+        let ys = self.instantiate(&metas, im_tys)?;
+        Ok(self.unify_all(&xs, &ys))
+    }
+
+    fn implementor_of(&mut self, im: &Var) -> Result<(Tele<Term>, Vec<Term>), Error> {
+        use Body::*;
+        let im_def = self.sigma.get(im).unwrap();
+        let tele = im_def.tele.clone();
+        match &im_def.body {
+            Implements(body) => Ok((tele, body.implementor_types(self.sigma)?)),
+            _ => unreachable!(),
+        }
+    }
+
+    fn implements_metas(&mut self, tele: &Tele<Term>) -> Vec<(Var, Term)> {
+        let mut metas = Vec::default();
+        for p in tele {
+            let is_pred = matches!(p.typ.as_ref(), Term::ImplementsOf(_, _));
+            if !is_pred && Self::auto_implicit(&p.typ).is_none() {
+                metas.push((p.var.clone(), self.implements_meta(p)));
+            }
+        }
+        metas
+    }
+
+    fn instantiate(&mut self, metas: &[(Var, Term)], tys: Vec<Term>) -> Result<Vec<Term>, Error> {
+        let rho = metas.iter().map(|(v, m)| (v, m)).collect::<Vec<_>>();
+        let mut ys = Vec::default();
+        for ty in tys {
+            ys.push(Normalizer::new(self.sigma, self.loc).with(&rho, ty)?);
+        }
+        Ok(ys)
+    }
+
+    /// Removes the metas of an implementation once its matching attempt is over, since the found
+    /// arguments are already normalized with their solutions.
+    fn remove_metas(&mut self, metas: Vec<(Var, Term)>) {
+        for (_, m) in metas {
+            if let Term::MetaRef(_, v, _) = m {
+                self.sigma.remove(&v);
+            }
+        }
+    }
+
+    fn implements_meta(&mut self, p: &Param<Term>) -> Term {
+        use Body::*;
+        let v = Var::new(p.var.as_str());
+        self.sigma.insert(
+            v.clone(),
+            Def {
+                loc: self.loc,
+                name: v.clone(),
+                is_exported: false,
+                tele: Default::default(),
+                ret: p.typ.clone(),
+                body: Meta(InsertedMeta, None),
+            },
+        );
+        Term::MetaRef(InsertedMeta, v, Default::default())
+    }
+
+    fn discharge(&mut self, i: &Var, ts: &[Term], pts: &[Term], pi: &Var) -> Result<Term, Error> {
+        let constraint = format!("{pi}<{}>", Self::implementors(pts));
+        let ret = if Self::is_stuck(pts) {
+            self.assumption(pts, pi)
+        } else {
+            if self.depth == MAX_CONSTRAINT_DEPTH {
+                return Err(ConstraintDepthExceeded(constraint, self.loc));
+            }
+            self.depth += 1;
+            let ret = self.check_constraint(pts, pi);
+            self.depth -= 1;
+            ret
+        };
+        ret.map_err(|e| match e {
+            UnresolvedImplementation(_, _, _) => {
+                UnsatisfiedConstraint(constraint, i.clone(), Self::implementors(ts), self.loc)
+            }
+            e => e,
+        })
+    }
+
+    /// Builds the dictionary for the constraint `ts` implementing `i`, where the stuck constraints
+    /// are satisfied by the predicates in `tele`.
+    pub fn evidence(&mut self, tele: &Tele<Term>, ts: &[Term], i: &Var) -> Result<Term, Error> {
+        use Term::*;
+
+        let assumptions = tele
+            .iter()
+            .filter_map(|p| match p.typ.as_ref() {
+                ImplementsOf(xs, j) => Some((xs.clone(), j.clone(), Ref(p.var.clone()))),
+                _ => None,
+            })
+            .collect();
+        let saved = self.assumptions.replace(assumptions);
+        let ret = if Self::is_stuck(ts) {
+            self.assumption(ts, i)
+        } else {
+            self.check_constraint(ts, i)
+        };
+        self.assumptions = saved;
+        ret
+    }
+
+    /// Finds the predicate satisfying the stuck constraint that `ts` implement `i`. Outside of the
+    /// evidence of a constraint, the dictionary is found once `ts` are known, or by the predicates
+    /// in scope when erased.
+    fn assumption(&mut self, ts: &[Term], i: &Var) -> Result<Term, Error> {
+        let assumptions = match &self.assumptions {
+            Some(assumptions) => assumptions.clone(),
+            None => return Ok(Term::FindDict(ts.to_vec(), i.clone())),
+        };
+        for (xs, j, d) in assumptions {
+            let path = match &j == i {
+                true => Vec::default(),
+                false => match super_path(self.sigma, &j, i) {
+                    Some(path) => path,
+                    None => continue,
+                },
             };
-            match Unifier::new(self.sigma, self.loc).unify(&y, x) {
-                Ok(_) => return Ok(()),
-                Err(_) => continue,
+            if self.unify_all(&xs, ts) {
+                // Superinterface dictionaries are reachable from the subinterface ones.
+                return Ok(path
+                    .into_iter()
+                    .fold(d, |d, s| Term::Access(Box::new(d), s.super_dict())));
             }
         }
-        Err(UnresolvedImplementation(x.clone(), self.loc))
+        Err(UnresolvedImplementation(
+            i.clone(),
+            Self::implementors(ts),
+            self.loc,
+        ))
+    }
+
+    fn implementors(ts: &[Term]) -> String {
+        ts.iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
     }
 
-    fn find_implementation(&mut self, ty: Term, i: Var, f: Var) -> Result<Term, Error> {
+    fn find_implementation(&mut self, ts: Vec<Term>, i: Var, f: Var) -> Result<Term, Error> {
         use Body::*;
 
-        let ims = match &self.sigma.get(&i).unwrap().body {
-            Interface { ims, .. } => ims.clone(),
+        let (ims, default) = match &self.sigma.get(&i).unwrap().body {
+            Interface { ims, defaults, .. } => (ims.clone(), defaults.get(&f).cloned()),
             _ => unreachable!(),
         };
 
         for im in ims.into_iter().rev() {
-            let (im_ty, im_fn) = match &self.sigma.get(&im).unwrap().body {
-                Implements(body) => (
-                    body.implementor_type(self.sigma)?,
-                    body.fns.get(&f).unwrap().clone(),
-                ),
+            let args = match self.implements_args(&i, &im, &ts)? {
+                Some(args) => args,
+                None => continue,
+            };
+            let im_fn = match &self.sigma.get(&im).unwrap().body {
+                Implements(body) => body.fns.get(&f).unwrap().clone(),
                 _ => unreachable!(),
             };
 
-            if Unifier::new(self.sigma, self.loc)
-                .unify(&ty, &im_ty)
-                .is_err()
-            {
-                continue;
+            let tm = self.sigma.get(&im_fn).unwrap().to_term(im_fn.clone());
+            if default.as_ref() == Some(&im_fn) {
+                let mut xs = ts;
+                xs.push(self.implements_dict(im, args));
+                return self.apply(tm, UnnamedImplicit, &xs);
             }
-
-            return Ok(self.sigma.get(&im_fn).unwrap().to_term(im_fn));
+            return self.apply(tm, UnnamedImplicit, &args);
         }
 
-        Err(UnresolvedImplementation(ty, self.loc))
+        Err(UnresolvedImplementation(
+            i,
+            Self::implementors(&ts),
+            self.loc,
+        ))
     }
 }
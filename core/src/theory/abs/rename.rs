@@ -42,8 +42,12 @@ impl Renamer {
                 Box::new(self.term(*t)),
                 Box::new(self.term(*e)),
             ),
+            StrAdd(a, b) => StrAdd(Box::new(self.term(*a)), Box::new(self.term(*b))),
+            StrQuote(a) => StrQuote(Box::new(self.term(*a))),
             NumAdd(a, b) => NumAdd(Box::new(self.term(*a)), Box::new(self.term(*b))),
             NumSub(a, b) => NumSub(Box::new(self.term(*a)), Box::new(self.term(*b))),
+            Array(t) => Array(Box::new(self.term(*t))),
+            Arr(xs) => Arr(xs.into_iter().map(|x| self.term(x)).collect()),
             Fields(fields) => {
                 let mut m = FieldMap::default();
                 for (f, tm) in fields {
@@ -52,6 +56,8 @@ impl Renamer {
                 Fields(m)
             }
             Combine(a, b) => Combine(Box::new(self.term(*a)), Box::new(self.term(*b))),
+            MapFields(r, f) => MapFields(Box::new(self.term(*r)), Box::new(self.term(*f))),
+            FieldNames(r) => FieldNames(Box::new(self.term(*r))),
             RowOrd(a, d, b) => RowOrd(Box::new(self.term(*a)), d, Box::new(self.term(*b))),
             RowEq(a, b) => RowEq(Box::new(self.term(*a)), Box::new(self.term(*b))),
             Object(f) => Object(Box::new(self.term(*f))),
@@ -59,6 +65,25 @@ impl Renamer {
             Concat(a, b) => Concat(Box::new(self.term(*a)), Box::new(self.term(*b))),
             Access(a, n) => Access(Box::new(self.term(*a)), n),
             Downcast(a, f) => Downcast(Box::new(self.term(*a)), Box::new(self.term(*f))),
+            Restrict(a, ns) => Restrict(Box::new(self.term(*a)), ns),
+            MapMethod(a, r, i, f) => {
+                MapMethod(Box::new(self.term(*a)), Box::new(self.term(*r)), i, f)
+            }
+            ObjectLet(ps, rest, a, b) => {
+                let a = self.term(*a); // not guarded by the bindings, rename it first
+                ObjectLet(
+                    ps.into_iter().map(|(n, p)| (n, self.param(p))).collect(),
+                    rest.map(|p| self.param(p)),
+                    Box::new(a),
+                    Box::new(self.term(*b)),
+                )
+            }
+            LocalFn(v, a, b) => LocalFn(v, Box::new(self.term(*a)), Box::new(self.term(*b))),
+            Optional(a) => Optional(Box::new(self.term(*a))),
+            Singleton(a) => Singleton(Box::new(self.term(*a))),
+            Union(ts) => Union(ts.into_iter().map(|t| self.term(t)).collect()),
+            OptionalAccess(a, n) => OptionalAccess(Box::new(self.term(*a)), n),
+            Update(a, f) => Update(Box::new(self.term(*a)), Box::new(self.term(*f))),
             Enum(f) => Enum(Box::new(self.term(*f))),
             Variant(f) => Variant(Box::new(self.term(*f))),
             Upcast(a, f) => Upcast(Box::new(self.term(*a)), Box::new(self.term(*f))),
@@ -71,11 +96,16 @@ impl Renamer {
                 Switch(Box::new(a), m)
             }
             Unionify(a) => Unionify(Box::new(self.term(*a))),
+            Nominal(r, ts) => Nominal(r, ts.into_iter().map(|t| self.term(t)).collect()),
             Vptr(r, ts) => Vptr(r, ts.into_iter().map(|t| self.term(t)).collect()),
             Vp(r, ts) => Vp(r, ts.into_iter().map(|t| self.term(t)).collect()),
             Lookup(a) => Lookup(Box::new(self.term(*a))),
-            Find(ty, i, f) => Find(Box::new(self.term(*ty)), i, f),
-            ImplementsOf(a, i) => ImplementsOf(Box::new(self.term(*a)), i),
+            Find(ts, i, f) => Find(ts.into_iter().map(|t| self.term(t)).collect(), i, f),
+            FindDict(ts, i) => FindDict(ts.into_iter().map(|t| self.term(t)).collect(), i),
+            ImplementsOf(ts, i) => ImplementsOf(ts.into_iter().map(|t| self.term(t)).collect(), i),
+            ImplementsDict(im, ds) => {
+                ImplementsDict(im, ds.into_iter().map(|t| self.term(t)).collect())
+            }
             tm => tm,
         }
     }
@@ -17,7 +17,11 @@ impl<'a> Unifier<'a> {
     }
 
     fn unify_err(&self, lhs: &Term, rhs: &Term) -> Result<(), Error> {
-        Err(NonUnifiable(lhs.clone(), rhs.clone(), self.loc))
+        Err(NonUnifiable(
+            Box::new(lhs.clone()),
+            Box::new(rhs.clone()),
+            self.loc,
+        ))
     }
 
     pub fn unify(&mut self, lhs: &Term, rhs: &Term) -> Result<(), Error> {
@@ -47,6 +51,15 @@ impl<'a> Unifier<'a> {
                 None => self.unify_err(lhs, rhs),
             },
 
+            // Forward-declared definitions are unfolded once defined.
+            (Undef(a), Undef(b)) if a == b => Ok(()),
+            (Undef(a), b) if !matches!(self.sigma.get(a).unwrap().body, Body::Undefined) => {
+                self.unify(&self.sigma.get(a).unwrap().to_term(a.clone()), b)
+            }
+            (a, Undef(b)) if !matches!(self.sigma.get(b).unwrap().body, Body::Undefined) => {
+                self.unify(a, &self.sigma.get(b).unwrap().to_term(b.clone()))
+            }
+
             (Qualified(_, a), Qualified(_, b)) if a == b => Ok(()),
             (Qualified(_, a), b) => match self.sigma.get(a) {
                 Some(d) => self.unify(&d.to_term(a.clone()), b),
@@ -105,7 +118,50 @@ impl<'a> Unifier<'a> {
                 self.unify(b, y)?;
                 self.unify(c, z)
             }
+            (Singleton(a), Singleton(b)) => self.unify(a, b),
+            (Union(_), Union(ys)) => {
+                for y in ys {
+                    self.unify(lhs, y)?;
+                }
+                Ok(())
+            }
+            (Union(xs), y @ Singleton(_)) => match xs.iter().any(|x| Self::is_same_literal(x, y)) {
+                true => Ok(()),
+                false => self.unify_err(lhs, rhs),
+            },
+            (String, Singleton(a)) if matches!(a.as_ref(), Str(_)) => Ok(()),
+            (Number, Singleton(a)) if matches!(a.as_ref(), Num(_)) => Ok(()),
+            (String | Number, Union(ys)) => {
+                for y in ys {
+                    self.unify(lhs, y)?;
+                }
+                Ok(())
+            }
+            (Optional(a), Optional(b)) => self.unify(a, b),
+            (Array(a), Array(b)) => self.unify(a, b),
             (Fields(a), Fields(b)) => self.unify_fields_eq(a, b),
+            (Combine(a, b), Combine(x, y)) => {
+                self.unify(a, x)?;
+                self.unify(b, y)
+            }
+            (Combine(a, b), Fields(y)) | (Fields(y), Combine(a, b)) => {
+                match (a.as_ref(), b.as_ref()) {
+                    (Fields(x), r) | (r, Fields(x)) => {
+                        self.unify_fields_ord(x, y)?;
+                        let rest = y
+                            .iter()
+                            .filter(|(n, _)| !x.contains_key(*n))
+                            .map(|(n, t)| (n.clone(), t.clone()))
+                            .collect();
+                        self.unify(r, &Fields(rest))
+                    }
+                    _ => self.unify_err(lhs, rhs),
+                }
+            }
+            (MapFields(r, f), MapFields(s, g)) => {
+                self.unify(r, s)?;
+                self.unify(f, g)
+            }
             (Object(a), Object(b)) => self.unify(a, b),
             (Obj(a), Obj(b)) => self.unify(a, b),
             (Enum(a), Enum(b)) => self.unify(a, b),
@@ -116,6 +172,12 @@ impl<'a> Unifier<'a> {
             (Num(a), Num(b)) if a == b => Ok(()),
             (Big(a), Big(b)) if a == b => Ok(()),
             (Vptr(a, _), Vptr(b, _)) if a == b => Ok(()),
+            (Nominal(a, xs), Nominal(b, ys)) if a == b => {
+                for (x, y) in xs.iter().zip(ys) {
+                    self.unify(x, y)?;
+                }
+                Ok(())
+            }
 
             (Univ, Univ) => Ok(()),
             (Unit, Unit) => Ok(()),
@@ -150,6 +212,8 @@ impl<'a> Unifier<'a> {
         let tele = d.tele.clone();
         let ret = d.ret.clone();
         match tm {
+            // Metas of implementation parameters are closed, and solved by the implementor types.
+            Ref(_) if tele.is_empty() => Ok(()),
             Ref(r) => match tele.into_iter().find(|p| &p.var == r) {
                 Some(p) => self.unify(&ret, &p.typ),
                 None => unreachable!(),
@@ -158,15 +222,30 @@ impl<'a> Unifier<'a> {
         }
     }
 
+    fn is_same_literal(x: &Term, y: &Term) -> bool {
+        use Term::*;
+        match (x, y) {
+            (Singleton(a), Singleton(b)) => match (a.as_ref(), b.as_ref()) {
+                (Str(a), Str(b)) => a == b,
+                (Num(a), Num(b)) => a == b,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
     pub fn unify_fields_ord(&mut self, small: &FieldMap, big: &FieldMap) -> Result<(), Error> {
         use Term::*;
         for (x, a) in small {
-            match big.get(x) {
-                Some(b) => self.unify(a, b)?,
-                None => {
+            // An optional field is satisfied by the field of the underlying type or its absence.
+            match (a, big.get(x)) {
+                (Optional(a), Some(b)) if !matches!(b, Optional(_)) => self.unify(a, b)?,
+                (a, Some(b)) => self.unify(a, b)?,
+                (Optional(_), None) => continue,
+                (_, None) => {
                     return Err(NonRowSat(
-                        Fields(small.clone()),
-                        Fields(big.clone()),
+                        Box::new(Fields(small.clone())),
+                        Box::new(Fields(big.clone())),
                         self.loc,
                     ))
                 }
@@ -177,14 +256,13 @@ impl<'a> Unifier<'a> {
 
     pub fn unify_fields_eq(&mut self, a: &FieldMap, b: &FieldMap) -> Result<(), Error> {
         use Term::*;
-        if a.len() != b.len() {
-            return self.unify_err(&Fields(a.clone()), &Fields(b.clone()));
-        }
         for (n, x) in a {
-            if let Some(y) = b.get(n) {
-                self.unify(x, y)?;
-                continue;
+            match b.get(n) {
+                Some(y) => self.unify(x, y)?,
+                None => return self.unify_err(&Fields(a.clone()), &Fields(b.clone())),
             }
+        }
+        if a.len() != b.len() {
             return self.unify_err(&Fields(a.clone()), &Fields(b.clone()));
         }
         Ok(())
@@ -1,6 +1,7 @@
 use std::fmt::{Display, Formatter};
 
 use crate::theory::abs::data::Dir;
+use crate::theory::abs::def::Def;
 use crate::theory::conc::load::ModuleID;
 use crate::theory::{Loc, Param, Syntax, Tele, Var};
 
@@ -66,6 +67,15 @@ pub enum Expr {
     Concat(Loc, Box<Self>, Box<Self>),
     Access(Loc, String),
     Downcast(Loc, Box<Self>),
+    Restrict(Loc, Box<Self>, Vec<String>),
+    MapMethod(Loc, Box<Self>, Box<Self>),
+    Update(Loc, Box<Self>, Box<Self>),
+    Optional(Loc, Box<Self>),
+    Singleton(Loc, Box<Self>),
+    Union(Loc, Vec<Self>),
+    OptionalAccess(Loc, Box<Self>, String),
+    ObjectLet(Loc, Vec<(String, Var)>, Option<Var>, Box<Self>, Box<Self>),
+    LocalFn(Loc, Box<Def<Self>>, Box<Self>),
 
     Enum(Loc, Box<Self>),
     Variant(Loc, String, Box<Self>),
@@ -78,6 +88,7 @@ pub enum Expr {
     Constraint(Loc, Box<Self>),
     Find(Loc, Var, Var),
     ImplementsOf(Loc, Box<Self>),
+    Derive(Loc, Box<Derivation>),
 }
 
 impl Expr {
@@ -138,6 +149,15 @@ impl Expr {
             Concat(loc, _, _) => loc,
             Access(loc, _) => loc,
             Downcast(loc, _) => loc,
+            Restrict(loc, _, _) => loc,
+            MapMethod(loc, _, _) => loc,
+            Update(loc, _, _) => loc,
+            Optional(loc, _) => loc,
+            Singleton(loc, _) => loc,
+            Union(loc, _) => loc,
+            OptionalAccess(loc, _, _) => loc,
+            ObjectLet(loc, _, _, _, _) => loc,
+            LocalFn(loc, _, _) => loc,
             Enum(loc, _) => loc,
             Variant(loc, _, _) => loc,
             Upcast(loc, _) => loc,
@@ -147,6 +167,7 @@ impl Expr {
             Constraint(loc, _) => loc,
             Find(loc, _, _) => loc,
             ImplementsOf(loc, _) => loc,
+            Derive(loc, _) => loc,
         }
     }
 
@@ -269,6 +290,34 @@ impl Display for Expr {
                 Concat(_, a, b) => format!("{a}...{b}"),
                 Access(_, n) => format!(".{n}"),
                 Downcast(_, a) => format!("{{...{a}}}"),
+                Restrict(_, a, ns) => format!(
+                    "omit({a}, {})",
+                    ns.iter()
+                        .map(|n| format!("\"{n}\""))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                MapMethod(_, a, f) => format!("mapFields({a}, {f})"),
+                Update(_, a, f) => format!("{{...{a}, {f}}}"),
+                Optional(_, a) => format!("{a}?"),
+                Singleton(_, a) => a.to_string(),
+                Union(_, ts) => ts
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+                OptionalAccess(_, a, n) => format!("{a}?.{n}"),
+                ObjectLet(_, fs, rest, a, b) => {
+                    let mut ps = fs
+                        .iter()
+                        .map(|(n, v)| format!("{n}: {v}"))
+                        .collect::<Vec<_>>();
+                    if let Some(r) = rest {
+                        ps.push(format!("...{r}"));
+                    }
+                    format!("let {{{}}} = {a};\n\t{b}", ps.join(", "))
+                }
+                LocalFn(_, d, b) => format!("{d}\n\t{b}"),
                 Enum(_, r) => format!("[{r}]"),
                 Variant(_, n, a) => format!("{n}({a})"),
                 Upcast(_, a) => format!("[...{a}]"),
@@ -290,8 +339,29 @@ impl Display for Expr {
                 Constraint(_, r) => r.to_string(),
                 Find(_, i, f) => format!("{i}.{f}"),
                 ImplementsOf(_, a) => a.to_string(),
+                Derive(_, d) => format!("derive({})", d.i),
             }
             .as_str(),
         )
     }
 }
+
+/// An implementation derived from the shape of its implementor, generated once the signature of
+/// the interface is known.
+#[derive(Debug, Clone)]
+pub struct Derivation {
+    pub implements: Var,
+    pub i: Var,
+    pub typ: Expr,
+    pub shape: Derived,
+    pub concat: Expr,
+    pub quote: Expr,
+}
+
+/// The shape of a type that implementations are derived from.
+#[derive(Debug, Clone)]
+pub enum Derived {
+    Object(Vec<(String, Expr)>),
+    Enum(Vec<(String, Expr)>),
+    Class(Var, Box<Expr>, Vec<(String, Expr)>),
+}
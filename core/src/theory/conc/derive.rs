@@ -0,0 +1,305 @@
+use crate::theory::conc::data::ArgInfo::UnnamedExplicit;
+use crate::theory::conc::data::{Derivation, Derived, Expr};
+use crate::theory::ParamInfo::Explicit;
+use crate::theory::{Loc, Param, Var};
+
+/// How the function of a derived implementation combines the fields or variants of the
+/// implementor, chosen by the signature of the interface method.
+#[derive(Debug, Copy, Clone)]
+pub enum Strategy {
+    /// `(T, T) -> boolean`.
+    Eq,
+    /// `(T) -> T`.
+    Clone,
+    /// `(T) -> string`.
+    Show,
+    /// `(T) -> string` of the prelude interface `Json`, rendering strings as quoted and escaped.
+    Json,
+}
+
+impl Strategy {
+    fn arity(&self) -> usize {
+        match self {
+            Strategy::Eq => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// Generates the function of a derived implementation by structural recursion over the fields or
+/// variants, calling the interface method on each of them.
+pub struct Deriver<'a> {
+    loc: Loc,
+    d: &'a Derivation,
+    f: &'a Var,
+}
+
+impl<'a> Deriver<'a> {
+    pub fn new(loc: Loc, d: &'a Derivation, f: &'a Var) -> Self {
+        Self { loc, d, f }
+    }
+
+    /// Returns the tupled parameter, the return type and the body of the function.
+    pub fn func(&self, s: Strategy) -> (Param<Expr>, Expr, Expr) {
+        use Expr::*;
+
+        let loc = self.loc;
+        let params = ["a", "b"]
+            .into_iter()
+            .take(s.arity())
+            .map(Var::new)
+            .collect::<Vec<_>>();
+        let (ret, mut body) = match s {
+            Strategy::Eq => (Boolean(loc), self.eq(&params[0], &params[1])),
+            Strategy::Clone => (self.d.typ.clone(), self.clone(&params[0])),
+            Strategy::Show => (String(loc), self.show(&params[0])),
+            Strategy::Json => (String(loc), self.json(&params[0])),
+        };
+
+        let tupled = Var::tupled();
+        let mut rhs = vec![tupled.clone()];
+        rhs.extend(params.iter().map(Var::untupled_rhs));
+        for (i, p) in params.iter().enumerate().rev() {
+            body = TupleLet(
+                loc,
+                p.clone(),
+                rhs[i + 1].clone(),
+                Box::new(Self::var(loc, &rhs[i])),
+                Box::new(body),
+            );
+        }
+        let typ = params.iter().rfold(Unit(loc), |b, p| {
+            let p = Param {
+                var: p.clone(),
+                info: Explicit,
+                typ: Box::new(self.d.typ.clone()),
+            };
+            Sigma(loc, p, Box::new(b))
+        });
+        let param = Param {
+            var: tupled,
+            info: Explicit,
+            typ: Box::new(typ),
+        };
+
+        (param, ret, body)
+    }
+
+    fn eq(&self, a: &Var, b: &Var) -> Expr {
+        use Expr::*;
+        let loc = self.loc;
+        match &self.d.shape {
+            Derived::Object(fields) | Derived::Class(_, _, fields) => {
+                fields.iter().rfold(True(loc), |e, (n, t)| {
+                    let loc = t.loc();
+                    let p = self.call(loc, vec![Self::access(loc, a, n), Self::access(loc, b, n)]);
+                    If(loc, Box::new(p), Box::new(e), Box::new(False(loc)))
+                })
+            }
+            Derived::Enum(variants) => {
+                let x = Var::new("x");
+                let y = Var::new("y");
+                let cases = variants
+                    .iter()
+                    .map(|(n, t)| {
+                        let cases = variants
+                            .iter()
+                            .map(|(m, _)| match t {
+                                _ if n != m => (m.clone(), Var::unbound(), False(loc)),
+                                Unit(_) => (m.clone(), Var::unbound(), True(loc)),
+                                _ => {
+                                    let loc = t.loc();
+                                    let args = vec![Self::var(loc, &x), Self::var(loc, &y)];
+                                    (m.clone(), y.clone(), self.call(loc, args))
+                                }
+                            })
+                            .collect();
+                        let e = Switch(loc, Box::new(Self::var(loc, b)), cases);
+                        (n.clone(), Self::payload(t, &x), e)
+                    })
+                    .collect();
+                Switch(loc, Box::new(Self::var(loc, a)), cases)
+            }
+        }
+    }
+
+    fn show(&self, a: &Var) -> Expr {
+        use Expr::*;
+        let loc = self.loc;
+        match &self.d.shape {
+            Derived::Object(fields) | Derived::Class(_, _, fields) => {
+                let mut parts = Vec::default();
+                let mut s = match &self.d.shape {
+                    Derived::Class(name, _, _) => format!("{name} {{"),
+                    _ => "{".to_string(),
+                };
+                for (i, (n, t)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        s.push_str(", ");
+                    }
+                    s.push_str(format!("{n}: ").as_str());
+                    parts.push(Str(loc, s));
+                    let loc = t.loc();
+                    parts.push(self.call(loc, vec![Self::access(loc, a, n)]));
+                    s = Default::default();
+                }
+                parts.push(Str(loc, "}".to_string()));
+                self.str_concat(parts)
+            }
+            Derived::Enum(variants) => {
+                let x = Var::new("x");
+                let cases = variants
+                    .iter()
+                    .map(|(n, t)| {
+                        let e = match t {
+                            Unit(_) => Str(loc, n.clone()),
+                            _ => self.str_concat(vec![
+                                Str(loc, format!("{n}(")),
+                                self.call(t.loc(), vec![Self::var(loc, &x)]),
+                                Str(loc, ")".to_string()),
+                            ]),
+                        };
+                        (n.clone(), Self::payload(t, &x), e)
+                    })
+                    .collect();
+                Switch(loc, Box::new(Self::var(loc, a)), cases)
+            }
+        }
+    }
+
+    fn clone(&self, a: &Var) -> Expr {
+        use Expr::*;
+        let loc = self.loc;
+        let clone = |t: &Expr, e| self.call(t.loc(), vec![e]);
+        match &self.d.shape {
+            Derived::Object(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(n, t)| (n.clone(), clone(t, Self::access(loc, a, n))))
+                    .collect();
+                Obj(loc, Box::new(Fields(loc, fields)))
+            }
+            Derived::Class(_, ctor, fields) => {
+                let args = fields
+                    .iter()
+                    .map(|(n, t)| clone(t, Self::access(loc, a, n)))
+                    .collect();
+                App(
+                    loc,
+                    ctor.clone(),
+                    UnnamedExplicit,
+                    Box::new(Self::tuple(loc, args)),
+                )
+            }
+            Derived::Enum(variants) => {
+                let x = Var::new("x");
+                let cases = variants
+                    .iter()
+                    .map(|(n, t)| {
+                        let payload = match t {
+                            Unit(_) => TT(loc),
+                            _ => clone(t, Self::var(loc, &x)),
+                        };
+                        let e = Variant(loc, n.clone(), Box::new(payload));
+                        (n.clone(), Self::payload(t, &x), e)
+                    })
+                    .collect();
+                Switch(loc, Box::new(Self::var(loc, a)), cases)
+            }
+        }
+    }
+
+    fn json(&self, a: &Var) -> Expr {
+        use Expr::*;
+        let loc = self.loc;
+        match &self.d.shape {
+            Derived::Object(fields) | Derived::Class(_, _, fields) => {
+                let mut parts = Vec::default();
+                for (i, (n, t)) in fields.iter().enumerate() {
+                    let sep = if i == 0 { "{" } else { "," };
+                    parts.push(Str(loc, format!("{sep}\"{n}\":")));
+                    parts.push(self.json_value(t, Self::access(t.loc(), a, n)));
+                }
+                if fields.is_empty() {
+                    parts.push(Str(loc, "{".to_string()));
+                }
+                parts.push(Str(loc, "}".to_string()));
+                self.str_concat(parts)
+            }
+            Derived::Enum(variants) => {
+                let x = Var::new("x");
+                let cases = variants
+                    .iter()
+                    .map(|(n, t)| {
+                        let e = match t {
+                            Unit(_) => Str(loc, format!("\"{n}\"")),
+                            _ => self.str_concat(vec![
+                                Str(loc, format!("{{\"{n}\":")),
+                                self.json_value(t, Self::var(loc, &x)),
+                                Str(loc, "}".to_string()),
+                            ]),
+                        };
+                        (n.clone(), Self::payload(t, &x), e)
+                    })
+                    .collect();
+                Switch(loc, Box::new(Self::var(loc, a)), cases)
+            }
+        }
+    }
+
+    /// Strings are quoted and escaped directly, other values are rendered by the method.
+    fn json_value(&self, t: &Expr, e: Expr) -> Expr {
+        let loc = t.loc();
+        match t {
+            Expr::String(_) => Self::apply(loc, &self.d.quote, vec![e]),
+            _ => self.call(loc, vec![e]),
+        }
+    }
+
+    fn payload(t: &Expr, x: &Var) -> Var {
+        match t {
+            Expr::Unit(_) => Var::unbound(),
+            _ => x.clone(),
+        }
+    }
+
+    fn var(loc: Loc, v: &Var) -> Expr {
+        Expr::Resolved(loc, v.clone())
+    }
+
+    fn access(loc: Loc, a: &Var, n: &str) -> Expr {
+        use Expr::*;
+        App(
+            loc,
+            Box::new(Access(loc, n.to_string())),
+            UnnamedExplicit,
+            Box::new(Self::var(loc, a)),
+        )
+    }
+
+    fn tuple(loc: Loc, args: Vec<Expr>) -> Expr {
+        use Expr::*;
+        args.into_iter()
+            .rfold(TT(loc), |a, x| Tuple(loc, Box::new(x), Box::new(a)))
+    }
+
+    fn call(&self, loc: Loc, args: Vec<Expr>) -> Expr {
+        Self::apply(loc, &Self::var(loc, self.f), args)
+    }
+
+    fn apply(loc: Loc, f: &Expr, args: Vec<Expr>) -> Expr {
+        Expr::App(
+            loc,
+            Box::new(f.clone()),
+            UnnamedExplicit,
+            Box::new(Self::tuple(loc, args)),
+        )
+    }
+
+    fn str_concat(&self, parts: Vec<Expr>) -> Expr {
+        parts
+            .into_iter()
+            .reduce(|a, b| Self::apply(self.loc, &self.d.concat, vec![a, b]))
+            .unwrap()
+    }
+}
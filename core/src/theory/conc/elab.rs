@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 use crate::maybe_grow;
 use crate::theory::abs::data::Dir::Le;
 use crate::theory::abs::data::{CaseMap, FieldMap, MetaKind, Term};
@@ -6,14 +8,19 @@ use crate::theory::abs::def::{Def, Gamma, Sigma};
 use crate::theory::abs::normalize::Normalizer;
 use crate::theory::abs::rename::rename;
 use crate::theory::abs::unify::Unifier;
-use crate::theory::conc::data::ArgInfo::{NamedImplicit, UnnamedExplicit};
-use crate::theory::conc::data::{ArgInfo, Expr};
+use crate::theory::conc::data::ArgInfo::{NamedImplicit, UnnamedExplicit, UnnamedImplicit};
+use crate::theory::conc::data::{ArgInfo, Derived, Expr};
+use crate::theory::conc::derive::{Deriver, Strategy};
+use crate::theory::conc::resolve::{NameMap, ResolvedVar};
 use crate::theory::ParamInfo::{Explicit, Implicit};
-use crate::theory::{Loc, Param, Tele, Var, VarGen, VPTR};
+use crate::theory::{Loc, Param, Tele, Var, VarGen, TUPLED, VPTR};
 use crate::Error;
 use crate::Error::{
     ExpectedClass, ExpectedEnum, ExpectedImplementsOf, ExpectedInterface, ExpectedObject,
-    ExpectedPi, ExpectedSigma, FieldsUnknown, NonExhaustive, UnresolvedField,
+    ExpectedPi, ExpectedSigma, FailedAssertion, FieldsUnknown, MismatchedImplementors,
+    MismatchedMethod, MissingMethod, MissingSuperImplementation, NonConforming, NonExhaustive,
+    NonUnifiable, OverlappingImplementations, PrivateMember, UnannotatedRecursion,
+    UnderivableInterface, UnmappableMethod, UnresolvedField, UnresolvedImplementation,
     UnresolvedImplicitParam,
 };
 
@@ -22,28 +29,74 @@ pub struct Elaborator {
     pub sigma: Sigma,
     gamma: Gamma,
     vg: VarGen,
+    owners: HashMap<Var, Var>,
+    owner: Option<Var>,
+    privates: HashMap<Var, Vec<String>>,
+    derivations: HashMap<Var, Vec<(String, Var)>>,
+    forwarded: Vec<Def<Term>>,
+    json: Option<Var>,
+    file_defs: HashSet<Var>,
+    inferring: HashSet<Var>,
 }
 
 impl Elaborator {
-    pub fn defs(&mut self, defs: Vec<Def<Expr>>) -> Result<Vec<Def<Term>>, Error> {
+    /// Remembers the prelude interfaces whose derivations are built in.
+    pub fn prelude(&mut self, builtins: &NameMap) {
+        self.json = builtins.get("Json").map(|ResolvedVar(_, v)| v.clone());
+    }
+
+    pub fn defs(&mut self, sccs: Vec<Vec<Def<Expr>>>) -> Result<Vec<Def<Term>>, Error> {
+        self.file_defs = sccs.iter().flatten().map(|d| d.name.clone()).collect();
         let mut ret = Vec::default();
-        for d in defs {
-            ret.push(self.def(d)?);
+        for scc in sccs {
+            if scc.len() > 1 {
+                // Types are declared first, since the signatures of the others might refer to them.
+                let (types, others): (Vec<_>, Vec<_>) = scc.iter().partition(|d| {
+                    matches!(
+                        d.body,
+                        Body::Class(_) | Body::Alias(_) | Body::NominalEnum(_) | Body::Newtype(_)
+                    )
+                });
+                for d in types.into_iter().chain(others) {
+                    self.declare(d)?;
+                }
+            }
+            for d in scc {
+                let d = self.def(d)?;
+                ret.append(&mut self.forwarded);
+                ret.push(d);
+            }
         }
         Ok(ret)
     }
 
-    fn def(&mut self, d: Def<Expr>) -> Result<Def<Term>, Error> {
+    /// Forward-declare the signature of a mutually recursive definition.
+    fn declare(&mut self, d: &Def<Expr>) -> Result<(), Error> {
+        self.owner = self.owners.get(&d.name).cloned();
+        let (checked, _) = self.signature(d)?;
+        for n in checked {
+            self.gamma.remove(&n);
+        }
+        Ok(())
+    }
+
+    fn signature(&mut self, d: &Def<Expr>) -> Result<(Vec<Var>, Term), Error> {
         use Body::*;
 
+        let is_implements = matches!(d.body, Implements(_) | ImplementsFn(_));
         let mut checked = Vec::default();
         let mut tele = Tele::default();
-        for p in d.tele {
+        for p in d.tele.clone() {
             let gamma_var = p.var.clone();
             let checked_var = p.var.clone();
             let var = p.var.clone();
 
-            let gamma_typ = self.check(*p.typ, &Term::Univ)?;
+            let gamma_typ = match *p.typ {
+                Expr::ImplementsOf(loc, a) if is_implements || self.is_row_pred(&a) => {
+                    self.implements_pred(loc, *a)?
+                }
+                typ => self.check(typ, &Term::Univ)?,
+            };
             let typ = Box::new(gamma_typ.clone());
 
             self.gamma.insert(gamma_var, Box::new(gamma_typ));
@@ -55,23 +108,88 @@ impl Elaborator {
             })
         }
 
-        let ret = self.check(*d.ret, &Term::Univ)?;
+        // The return type of an unannotated function is a meta, solved by checking its body.
+        if matches!((&d.body, &*d.ret), (Fn(_), Expr::InsertedHole(_))) {
+            self.inferring.insert(d.name.clone());
+        }
+        let ret = self.check(*d.ret.clone(), &Term::Univ)?;
         self.sigma.insert(
             d.name.clone(),
             Def {
                 loc: d.loc,
                 name: d.name.clone(),
+                is_exported: d.is_exported,
                 tele,
                 ret: Box::new(ret.clone()),
                 body: Undefined,
             },
         );
 
+        Ok((checked, ret))
+    }
+
+    fn def(&mut self, d: Def<Expr>) -> Result<Def<Term>, Error> {
+        use Body::*;
+
+        let d = self.derive(d)?;
+        self.owner = self.owners.get(&d.name).cloned();
+
+        let (checked, ret) = match self.sigma.get(&d.name) {
+            Some(declared) => {
+                let declared = declared.clone();
+                for p in &declared.tele {
+                    self.gamma.insert(p.var.clone(), p.typ.clone());
+                }
+                let checked = declared.tele.into_iter().map(|p| p.var).collect();
+                (checked, *declared.ret)
+            }
+            None => self.signature(&d)?,
+        };
+
         let mut inferred_ret = None;
         let body = match d.body {
             Fn(f) => Fn(self.check(f, &ret)?),
             Postulate => Postulate,
             Alias(t) => Alias(self.check(t, &ret)?),
+            NominalEnum(t) => NominalEnum(self.check(t, &ret)?),
+            Newtype(t) => Newtype(self.check(t, &ret)?),
+            Coerce => Coerce,
+            StaticAssert(a, b) => {
+                let metas = self.metas();
+                let a = self.check(a, &Term::Univ)?;
+                let b = self.check(b, &Term::Univ)?;
+                let a = Normalizer::new(&mut self.sigma, d.loc).term(a)?;
+                let b = Normalizer::new(&mut self.sigma, d.loc).term(b)?;
+                // Holes would be solved by the assertion instead of being asserted.
+                if self.metas().difference(&metas).next().is_some() {
+                    let diff = ", holes are not allowed".to_string();
+                    return Err(FailedAssertion(Box::new((a, b)), diff, d.loc));
+                }
+                // Unification accepts subtypes on either side, so equality requires both directions.
+                let diff = |e, swap| {
+                    let (x, y) = match e {
+                        NonUnifiable(x, y, _) if swap => (y, x),
+                        NonUnifiable(x, y, _) => (x, y),
+                        e => return format!(", {e}"),
+                    };
+                    match (x.to_string(), y.to_string()) == (a.to_string(), b.to_string()) {
+                        true => Default::default(),
+                        false => format!(", differing at \"{x}\" and \"{y}\""),
+                    }
+                };
+                Unifier::new(&mut self.sigma, d.loc)
+                    .unify(&a, &b)
+                    .map_err(|e| diff(e, false))
+                    .and_then(|_| {
+                        Unifier::new(&mut self.sigma, d.loc)
+                            .unify(&b, &a)
+                            .map_err(|e| diff(e, true))
+                    })
+                    .map_err(|diff| {
+                        FailedAssertion(Box::new((a.clone(), b.clone())), diff, d.loc)
+                    })?;
+                StaticAssert(a, b)
+            }
             Const(anno, f) => Const(
                 anno,
                 if anno {
@@ -83,15 +201,30 @@ impl Elaborator {
                 },
             ),
 
-            Class(body) => Class(Box::new(ClassBody {
-                object: self.check(body.object, &ret)?,
-                methods: body.methods,
-                ctor: body.ctor,
-                vptr: body.vptr,
-                vptr_ctor: body.vptr_ctor,
-                vtbl: body.vtbl,
-                vtbl_lookup: body.vtbl_lookup,
-            })),
+            Class(body) => {
+                let owned = body
+                    .methods
+                    .iter()
+                    .map(|(_, m)| m)
+                    .chain(body.statics.iter())
+                    .chain([&body.ctor]);
+                for v in owned {
+                    self.owners.insert(v.clone(), body.vtbl_lookup.clone());
+                }
+                self.privates
+                    .insert(body.vtbl_lookup.clone(), body.privates.clone());
+                Class(Box::new(ClassBody {
+                    object: self.check(body.object, &ret)?,
+                    methods: body.methods,
+                    statics: body.statics,
+                    privates: body.privates,
+                    ctor: body.ctor,
+                    vptr: body.vptr,
+                    vptr_ctor: body.vptr_ctor,
+                    vtbl: body.vtbl,
+                    vtbl_lookup: body.vtbl_lookup,
+                }))
+            }
             Ctor(f) => Ctor(self.check(f, &ret)?),
             Method(f) => Method(self.check(f, &ret)?),
             VptrType(t) => VptrType(self.check(t, &ret)?),
@@ -99,15 +232,36 @@ impl Elaborator {
             VtblType(t) => VtblType(self.check(t, &ret)?),
             VtblLookup => VtblLookup,
 
-            Interface { fns, ims } => Interface { fns, ims },
+            Interface {
+                fns,
+                ims,
+                defaults,
+                supers,
+            } => {
+                for s in &supers {
+                    if !matches!(self.sigma.get(s).unwrap().body, Interface { .. }) {
+                        return Err(ExpectedInterface(Term::Ref(s.clone()), d.loc));
+                    }
+                }
+                Interface {
+                    fns,
+                    ims,
+                    defaults,
+                    supers,
+                }
+            }
             Implements(body) => Implements(self.check_implements_body(&d.name, *body)?),
             ImplementsFn(f) => ImplementsFn(self.check(f, &ret)?),
-            Findable(i) => Findable(i),
+            Findable(i, n) => Findable(i, n),
 
             Undefined => unreachable!(),
             Meta(_, _) => unreachable!(),
         };
 
+        if self.inferring.remove(&d.name) {
+            inferred_ret = Some(Box::new(Normalizer::new(&mut self.sigma, d.loc).term(ret)?));
+        }
+
         for n in checked {
             self.gamma.remove(&n);
         }
@@ -121,6 +275,75 @@ impl Elaborator {
         Ok(checked.clone())
     }
 
+    /// Expands a derived implementation into the function of the single method of its interface.
+    fn derive(&mut self, d: Def<Expr>) -> Result<Def<Expr>, Error> {
+        use Body::*;
+
+        let (loc, x) = match d.body {
+            ImplementsFn(Expr::Derive(loc, x)) => (loc, *x),
+            body => return Ok(Def { body, ..d }),
+        };
+        let f = match &self.sigma.get(&x.i).unwrap().body {
+            Interface { fns, .. } if fns.len() == 1 => fns[0].clone(),
+            Interface { .. } => return Err(UnderivableInterface(x.i, loc)),
+            _ => return Err(ExpectedInterface(Term::Ref(x.i), loc)),
+        };
+        let s = match self.derive_strategy(&x.i, &f) {
+            Some(s) => s,
+            None => return Err(UnderivableInterface(x.i, loc)),
+        };
+        let (param, ret, body) = Deriver::new(loc, &x, &f).func(s);
+
+        let name = f.implement_func(&x.i, &[x.typ]);
+        self.derivations
+            .insert(x.implements, vec![(f.to_string(), name.clone())]);
+        if let Derived::Class(_, ctor, _) = &x.shape {
+            if let Expr::Resolved(_, ctor) = ctor.as_ref() {
+                if let Some(owner) = self.owners.get(ctor).cloned() {
+                    self.owners.insert(name.clone(), owner);
+                }
+            }
+        }
+
+        let mut tele = d.tele;
+        tele.push(param);
+        Ok(Def {
+            loc: d.loc,
+            name,
+            is_exported: d.is_exported,
+            tele,
+            ret: Box::new(ret),
+            body: ImplementsFn(body),
+        })
+    }
+
+    fn derive_strategy(&self, i: &Var, f: &Var) -> Option<Strategy> {
+        use Term::*;
+
+        let d = self.sigma.get(f).unwrap();
+        let (t, p) = match d.tele.as_slice() {
+            [t, p] => (t, p),
+            _ => return None,
+        };
+        let is_implementor = |ty: &Term| matches!(ty, Ref(r) if r == &t.var);
+        let mut arity = 0;
+        let mut ps = p.typ.as_ref();
+        while let Sigma(x, rest) = ps {
+            if !is_implementor(&x.typ) {
+                return None;
+            }
+            arity += 1;
+            ps = rest;
+        }
+        match (arity, d.ret.as_ref()) {
+            (2, Boolean) => Some(Strategy::Eq),
+            (1, ret) if is_implementor(ret) => Some(Strategy::Clone),
+            (1, String) if self.json.as_ref() == Some(i) => Some(Strategy::Json),
+            (1, String) => Some(Strategy::Show),
+            _ => None,
+        }
+    }
+
     fn check_implements_body(
         &mut self,
         d: &Var,
@@ -129,46 +352,374 @@ impl Elaborator {
         use Body::*;
         use Expr::*;
 
-        let (i, im) = body.i;
-        let ret = Box::new(ImplementsBody {
-            i: (i, Box::new(self.infer(*im, None)?.0)),
+        let im_def = self.sigma.get(d).unwrap();
+        let loc = im_def.loc;
+        let im_tele = im_def.tele.clone();
+        let (i, ims) = body.i;
+        let derivation = self.derivations.remove(d);
+        let class_methods = match ims.as_slice() {
+            [im] => self.class_methods(im),
+            _ => None,
+        };
+        let im_exprs = ims.clone();
+        let mut tms = Vec::default();
+        for im in ims {
+            tms.push(self.infer(im, None)?.0);
+        }
+        let mut ret = Box::new(ImplementsBody {
+            i: (i, tms),
             fns: body.fns,
+            supers: Default::default(),
         });
-        let im_tm = ret.implementor_type(&self.sigma)?;
+        let im_tms = ret.implementor_types(&self.sigma)?;
 
-        let i_def = self.sigma.get_mut(&ret.i.0).unwrap();
+        let i_def = self.sigma.get(&ret.i.0).unwrap();
         let i_def_loc = i_def.loc;
-        match &mut i_def.body {
-            Interface { fns, ims, .. } => {
+        if i_def.tele.len() != im_tms.len() {
+            return Err(MismatchedImplementors(
+                ret.i.0.clone(),
+                i_def.tele.len(),
+                im_tms.len(),
+                loc,
+            ));
+        }
+        let others = match &i_def.body {
+            Interface { ims, .. } => ims.clone(),
+            _ => Default::default(),
+        };
+        // Matching instantiates and solves metas, which must not leak into the checked definitions.
+        let mut scratch = match others.is_empty() {
+            true => Default::default(),
+            false => self.sigma.clone(),
+        };
+        for other in others {
+            if Normalizer::new(&mut scratch, loc).is_overlapping(
+                &im_tele,
+                im_tms.clone(),
+                &other,
+            )? {
+                let other_loc = self.sigma.get(&other).unwrap().loc;
+                return Err(OverlappingImplementations(
+                    ret.i.0.clone(),
+                    im_tms
+                        .iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    loc,
+                    self.file_defs.contains(&other).then_some(other_loc),
+                ));
+            }
+        }
+
+        let methods = match derivation {
+            Some(ms) => Some(ms),
+            None => match self.newtype_target(loc, &im_tms)? {
+                Some(t) if ret.fns.is_empty() => {
+                    let d_tele = self.sigma.get(d).unwrap().tele.clone();
+                    let i = ret.i.0.clone();
+                    Some(self.forward_methods(loc, &i, &im_exprs, &d_tele, &im_tms[0], t)?)
+                }
+                _ => class_methods,
+            },
+        };
+
+        let i_def = self.sigma.get_mut(&ret.i.0).unwrap();
+        let mut defaulted = Vec::default();
+        // Missing and mismatched methods are all reported at once.
+        let mut errors = Vec::default();
+        let supers = match &mut i_def.body {
+            Interface {
+                fns,
+                ims,
+                defaults,
+                supers,
+            } => {
                 ims.push(d.clone());
                 for f in fns {
                     if ret.fns.contains_key(f) {
                         continue;
                     }
-                    return Err(NonExhaustive(im_tm, i_def_loc));
+                    let m = methods
+                        .as_ref()
+                        .and_then(|ms| ms.iter().find(|(n, _)| n == f.as_str()));
+                    match (m, defaults.get(f)) {
+                        (Some((_, m)), _) => ret.fns.insert(f.clone(), m.clone()),
+                        (None, Some(default)) => {
+                            defaulted.push(f.clone());
+                            ret.fns.insert(f.clone(), default.clone())
+                        }
+                        (None, None) => {
+                            errors.push(MissingMethod(f.clone(), ret.i.0.clone(), loc));
+                            continue;
+                        }
+                    };
                 }
+                supers.clone()
             }
             _ => return Err(ExpectedInterface(Term::Ref(ret.i.0.clone()), i_def_loc)),
         };
 
+        self.sigma.get_mut(d).unwrap().body = Implements(ret.clone());
+
+        let tele = self.sigma.get(d).unwrap().tele.clone();
+        for s in supers {
+            let dict = Normalizer::new(&mut self.sigma, loc)
+                .evidence(&tele, &im_tms, &s)
+                .map_err(|e| match e {
+                    UnresolvedImplementation(_, im, _) => {
+                        MissingSuperImplementation(s.clone(), im, loc)
+                    }
+                    e => e,
+                })?;
+            ret.supers.push((s, dict));
+        }
+        self.sigma.get_mut(d).unwrap().body = Implements(ret.clone());
+
+        let mut mismatched = Vec::default();
         for (i_fn, im_fn) in &ret.fns {
+            if defaulted.contains(i_fn) {
+                continue;
+            }
             let i_fn_def = self.sigma.get(i_fn).unwrap();
 
             let i_loc = i_fn_def.loc;
             let im_loc = self.sigma.get(im_fn).unwrap().loc;
 
-            let (i_fn_ty_p, i_fn_ty_b) = match i_fn_def.to_type() {
-                Term::Pi(p, b) => (p, b),
+            let mut i_fn_ty = i_fn_def.to_type();
+            let mut aliases = Vec::default();
+            for _ in &im_tms {
+                i_fn_ty = match i_fn_ty {
+                    Term::Pi(p, b) => {
+                        aliases.push(p.var);
+                        *b
+                    }
+                    _ => unreachable!(),
+                };
+            }
+            let rho = aliases.iter().zip(&im_tms).collect::<Vec<_>>();
+            let i_fn_ty_applied = Normalizer::new(&mut self.sigma, i_loc).with(&rho, i_fn_ty)?;
+            let (_, mut im_fn_ty) = self.infer(Resolved(im_loc, im_fn.clone()), None)?;
+            let mut im_vars = Vec::default();
+            for p in &im_tele {
+                im_fn_ty = match im_fn_ty {
+                    Term::Pi(q, b) => {
+                        im_vars.push((q.var, Term::Ref(p.var.clone())));
+                        *b
+                    }
+                    _ => unreachable!(),
+                };
+            }
+            let rho = im_vars.iter().map(|(v, t)| (v, t)).collect::<Vec<_>>();
+            let im_fn_ty = Normalizer::new(&mut self.sigma, im_loc).with(&rho, im_fn_ty)?;
+
+            if let Err(e) = Unifier::new(&mut self.sigma, im_loc).unify(&i_fn_ty_applied, &im_fn_ty)
+            {
+                let tys = Box::new((i_fn_ty_applied, im_fn_ty));
+                mismatched.push(MismatchedMethod(i_fn.clone(), tys, im_loc, Box::new(e)));
+            }
+        }
+        mismatched.sort_by_key(|e| match e {
+            MismatchedMethod(_, _, loc, _) => loc.start,
+            _ => unreachable!(),
+        });
+        errors.extend(mismatched);
+
+        match errors.len() {
+            0 => Ok(ret),
+            1 => Err(errors.pop().unwrap()),
+            _ => Err(NonConforming(ret.i.0.clone(), errors, loc)),
+        }
+    }
+
+    /// Predicates of implementations are only discharged when the implementation is chosen, since
+    /// they may refer to implementations not yet checked.
+    fn implements_pred(&mut self, loc: Loc, a: Expr) -> Result<Term, Error> {
+        use Expr::*;
+
+        let mut args = Vec::default();
+        let mut f = a.clone();
+        while let App(_, g, _, x) = f {
+            args.push(*x);
+            f = *g;
+        }
+        match f {
+            Resolved(_, i)
+                if matches!(
+                    self.sigma.get(&i).map(|d| &d.body),
+                    Some(Body::Interface { .. })
+                ) =>
+            {
+                let mut ts = Vec::default();
+                for x in args.into_iter().rev() {
+                    ts.push(self.infer(x, None)?.0);
+                }
+                Ok(Term::ImplementsOf(ts, i))
+            }
+            _ => self.check(ImplementsOf(loc, Box::new(a)), &Term::Univ),
+        }
+    }
+
+    /// Whether a constraint like `Show<'R>` ranges over the fields of a row.
+    fn is_row_pred(&self, a: &Expr) -> bool {
+        use Expr::*;
+
+        let mut f = a;
+        while let App(_, g, _, x) = f {
+            if let Resolved(_, r) = x.as_ref() {
+                if matches!(self.gamma.get(r).map(AsRef::as_ref), Some(Term::Row)) {
+                    return true;
+                }
+            }
+            f = g;
+        }
+        false
+    }
+
+    fn class_methods(&self, mut im: &Expr) -> Option<Vec<(String, Var)>> {
+        use Body::*;
+        use Expr::*;
+        while let App(_, f, _, _) = im {
+            im = f;
+        }
+        let v = match im {
+            Resolved(_, v) | Imported(_, v) | Qualified(_, _, v) => v,
+            _ => return None,
+        };
+        match &self.sigma.get(v)?.body {
+            Class(body) => Some(body.methods.clone()),
+            _ => None,
+        }
+    }
+
+    /// Local functions are checked as definitions, so that they are generalized over their
+    /// implicit parameters and may be recursive, and are referred to as such by the rest of the
+    /// body.
+    fn local_fn(&mut self, d: Def<Expr>) -> Result<(Var, Term), Error> {
+        let owner = self.owner.take();
+        let d = self.def(d);
+        self.owner = owner;
+        let d = d?;
+        let f = d.to_term(d.name.clone());
+        Ok((d.name, f))
+    }
+
+    fn metas(&self) -> HashSet<Var> {
+        self.sigma
+            .iter()
+            .filter(|(_, d)| matches!(d.body, Body::Meta(_, _)))
+            .map(|(v, _)| v.clone())
+            .collect()
+    }
+
+    /// Returns the underlying type of a newtype implementor, instantiated at its type arguments.
+    fn newtype_target(&mut self, loc: Loc, ims: &[Term]) -> Result<Option<Term>, Error> {
+        let im = match ims {
+            [im] => Normalizer::new(&mut self.sigma, loc).term(im.clone())?,
+            _ => return Ok(None),
+        };
+        let (n, ts) = match im {
+            Term::Nominal(n, ts) => (n, ts),
+            _ => return Ok(None),
+        };
+        let def = self.sigma.get(&n).unwrap();
+        let target = match &def.body {
+            Body::Newtype(t) => rename(Term::lam(&def.tele, t.clone())),
+            _ => return Ok(None),
+        };
+        Ok(Some(Normalizer::new(&mut self.sigma, loc).apply(
+            target,
+            UnnamedImplicit,
+            &ts,
+        )?))
+    }
+
+    /// Generates the functions implementing `i` for a newtype, which call the implementation for
+    /// the underlying type `target` with the same arguments, since both are the same at runtime.
+    fn forward_methods(
+        &mut self,
+        loc: Loc,
+        i: &Var,
+        ims: &[Expr],
+        im_tele: &Tele<Term>,
+        im: &Term,
+        target: Term,
+    ) -> Result<Vec<(String, Var)>, Error> {
+        use Term::*;
+
+        Normalizer::new(&mut self.sigma, loc).evidence(
+            im_tele,
+            std::slice::from_ref(&target),
+            i,
+        )?;
+
+        let fns = match &self.sigma.get(i).unwrap().body {
+            Body::Interface { fns, .. } => fns.clone(),
+            _ => return Err(ExpectedInterface(Ref(i.clone()), loc)),
+        };
+        let mut methods = Vec::default();
+        for f in fns {
+            let f_def = self.sigma.get(&f).unwrap();
+            let f_loc = f_def.loc;
+            let f_tm = f_def.to_term(f.clone());
+            let (alias, mut ty) = match f_def.to_type() {
+                Pi(p, b) => (p.var, *b),
                 _ => unreachable!(),
             };
-            let i_fn_ty_applied = Normalizer::new(&mut self.sigma, i_loc)
-                .with(&[(&i_fn_ty_p.var, &im_tm)], *i_fn_ty_b)?;
-            let (_, im_fn_ty) = self.infer(Resolved(im_loc, im_fn.clone()), None)?;
+            ty = rename(Normalizer::new(&mut self.sigma, f_loc).with(&[(&alias, im)], ty)?);
+            let mut body = Normalizer::new(&mut self.sigma, f_loc).apply(
+                f_tm,
+                UnnamedImplicit,
+                std::slice::from_ref(&target),
+            )?;
 
-            Unifier::new(&mut self.sigma, im_loc).unify(&i_fn_ty_applied, &im_fn_ty)?;
-        }
+            let mut tele = im_tele.clone();
+            let mut lets = Vec::default();
+            while let Pi(p, b) = ty {
+                let arg = match p.var.as_str() == TUPLED {
+                    true => {
+                        let mut xs = Vec::default();
+                        let mut rhs = p.var.clone();
+                        let mut rest = p.typ.as_ref();
+                        while let Sigma(x, r) = rest {
+                            let q = Param {
+                                var: x.var.untupled_rhs(),
+                                info: Explicit,
+                                typ: r.clone(),
+                            };
+                            lets.push((x.clone(), q.clone(), rhs));
+                            xs.push(Ref(x.var.clone()));
+                            rhs = q.var;
+                            rest = r;
+                        }
+                        xs.into_iter()
+                            .rfold(TT, |a, x| Tuple(Box::new(x), Box::new(a)))
+                    }
+                    false => Ref(p.var.clone()),
+                };
+                body =
+                    Normalizer::new(&mut self.sigma, f_loc).apply(body, p.info.into(), &[arg])?;
+                tele.push(p);
+                ty = *b;
+            }
+            for (x, q, rhs) in lets.into_iter().rev() {
+                body = TupleLet(x, q, Box::new(Ref(rhs)), Box::new(body));
+            }
 
-        Ok(ret)
+            let name = f.implement_func(i, ims);
+            let def = Def {
+                loc,
+                name: name.clone(),
+                is_exported: false,
+                tele,
+                ret: Box::new(ty),
+                body: Body::ImplementsFn(body),
+            };
+            self.sigma.insert(name.clone(), def.clone());
+            self.forwarded.push(def);
+            methods.push((f.to_string(), name));
+        }
+        Ok(methods)
     }
 
     fn check(&mut self, e: Expr, ty: &Term) -> Result<Term, Error> {
@@ -239,41 +790,163 @@ impl Elaborator {
                             info: Explicit,
                             typ,
                         };
-                        let b = self.guarded_check(&[&x, &y], *b, ty)?;
+                        let b = match (&a, ty) {
+                            (Term::Ref(v), Term::TupleLet(_, _, _, _)) => {
+                                let tm = Term::Tuple(
+                                    Box::new(Term::Ref(x.var.clone())),
+                                    Box::new(Term::Ref(y.var.clone())),
+                                );
+                                let ty = Normalizer::new(&mut self.sigma, a_loc)
+                                    .with(&[(v, &tm)], rename(ty.clone()))?;
+                                self.guarded_check(&[&x, &y], *b, &ty)?
+                            }
+                            _ => self.guarded_check(&[&x, &y], *b, ty)?,
+                        };
                         Term::TupleLet(x, y, Box::new(a), Box::new(b))
                     }
                     ty => return Err(ExpectedSigma(ty, a_loc)),
                 }
             }
+            ObjectLet(loc, fs, rest, a, b) => {
+                let (a, a_ty) = self.infer(*a, Some(ty))?;
+                let r = match Normalizer::new(&mut self.sigma, loc).term(a_ty)? {
+                    Term::Object(r) => *r,
+                    ty => return Err(ExpectedObject(ty, loc)),
+                };
+                let mut ps = Vec::default();
+                let mut remaining = r.clone();
+                for (n, var) in fs {
+                    if let Term::Fields(f) = &r {
+                        self.check_private(loc, &n, f)?;
+                    }
+                    let typ = Self::row_field(&r, &n).ok_or(UnresolvedField(
+                        n.clone(),
+                        Box::new(r.clone()),
+                        loc,
+                    ))?;
+                    remaining = Self::restrict_row(remaining, &n).ok_or(UnresolvedField(
+                        n.clone(),
+                        Box::new(r.clone()),
+                        loc,
+                    ))?;
+                    ps.push((
+                        n,
+                        Param {
+                            var,
+                            info: Explicit,
+                            typ: Box::new(typ),
+                        },
+                    ));
+                }
+                let rest = rest.map(|var| Param {
+                    var,
+                    info: Explicit,
+                    typ: Box::new(Term::Object(Box::new(remaining))),
+                });
+                let mut guards = ps.iter().map(|(_, p)| p).collect::<Vec<_>>();
+                guards.extend(rest.iter());
+                let b = self.guarded_check(&guards, *b, ty)?;
+                Term::ObjectLet(ps, rest, Box::new(a), Box::new(b))
+            }
             UnitLet(_, a, b) => Term::UnitLet(
                 Box::new(self.check(*a, &Term::Unit)?),
                 Box::new(self.check(*b, ty)?),
             ),
-            If(_, p, t, e) => Term::If(
-                Box::new(self.check(*p, &Term::Boolean)?),
-                Box::new(self.check(*t, ty)?),
-                Box::new(self.check(*e, ty)?),
-            ),
-            _ => {
-                let loc = e.loc();
-                let f_e = e.clone();
-
-                let (mut inferred_tm, inferred_ty) = self.infer(e, Some(ty))?;
-                let mut inferred = Normalizer::new(&mut self.sigma, loc).term(inferred_ty)?;
-                let expected = Normalizer::new(&mut self.sigma, loc).term(ty.clone())?;
-
-                if Self::is_hole_insertable(&expected) {
-                    if let Some(f_e) = Self::app_insert_holes(f_e, UnnamedExplicit, &inferred)? {
-                        let (new_tm, new_ty) = self.infer(f_e, Some(ty))?;
-                        inferred_tm = new_tm;
-                        inferred = new_ty;
-                    }
-                }
+            LocalFn(_, d, b) => {
+                let (v, f) = self.local_fn(*d)?;
+                Term::LocalFn(v, Box::new(f), Box::new(self.check(*b, ty)?))
+            }
+            If(_, p, t, e) => {
+                let p = self.check(*p, &Term::Boolean)?;
+                let (t, e) = match &p {
+                    Term::Ref(v) if self.gamma.contains_key(v) => (
+                        self.refined_check(v, Term::True, *t, ty)?,
+                        self.refined_check(v, Term::False, *e, ty)?,
+                    ),
+                    _ => (self.check(*t, ty)?, self.check(*e, ty)?),
+                };
+                Term::If(Box::new(p), Box::new(t), Box::new(e))
+            }
+            Obj(loc, r) => match Normalizer::new(&mut self.sigma, loc).term(ty.clone())? {
+                Term::Object(o) => match *o {
+                    Term::Fields(x) => self.check_obj(loc, *r, x)?,
+                    _ => self.check_inferred(Obj(loc, r), ty)?,
+                },
+                _ => self.check_inferred(Obj(loc, r), ty)?,
+            },
+            e => self.check_inferred(e, ty)?,
+        })
+    }
+
+    fn check_inferred(&mut self, e: Expr, ty: &Term) -> Result<Term, Error> {
+        let loc = e.loc();
+        let f_e = e.clone();
 
-                Unifier::new(&mut self.sigma, loc).unify(&expected, &inferred)?;
+        let (mut inferred_tm, inferred_ty) = self.infer(e, Some(ty))?;
+        let mut inferred = Normalizer::new(&mut self.sigma, loc).term(inferred_ty)?;
+        let expected = Normalizer::new(&mut self.sigma, loc).term(ty.clone())?;
 
-                inferred_tm
+        if matches!(expected, Term::Singleton(_) | Term::Union(_))
+            && matches!(inferred_tm, Term::Str(_) | Term::Num(_))
+        {
+            inferred = Term::Singleton(Box::new(inferred_tm.clone()));
+        }
+
+        if Self::is_hole_insertable(&expected) {
+            if let Some(f_e) = Self::app_insert_holes(f_e, UnnamedExplicit, &inferred)? {
+                let (new_tm, new_ty) = self.infer(f_e, Some(ty))?;
+                inferred_tm = new_tm;
+                inferred = new_ty;
             }
+        }
+
+        Unifier::new(&mut self.sigma, loc).unify(&expected, &inferred)?;
+
+        self.unstuck_find(loc, inferred_tm)
+    }
+
+    /// Checks an object against the fields of an object type, where an optional field is satisfied
+    /// either by its absence or by a value of the underlying type.
+    fn check_obj(&mut self, loc: Loc, r: Expr, x: FieldMap) -> Result<Term, Error> {
+        let fields = match r {
+            Expr::Fields(_, fields) => fields,
+            _ => unreachable!(),
+        };
+        let mut tm_fields = FieldMap::default();
+        let mut ty_fields = FieldMap::default();
+        for (n, e) in fields {
+            let (tm, ty) = match x.get(&n) {
+                Some(Term::Optional(t)) => (self.check(e, t)?, Term::Optional(t.clone())),
+                Some(t) => (self.check(e, t)?, t.clone()),
+                None => self.infer(e, None)?,
+            };
+            tm_fields.insert(n.clone(), tm);
+            ty_fields.insert(n, ty);
+        }
+        for (n, t) in &x {
+            if matches!(t, Term::Optional(_)) && !ty_fields.contains_key(n) {
+                ty_fields.insert(n.clone(), t.clone());
+            }
+        }
+        Unifier::new(&mut self.sigma, loc).unify(
+            &Term::Object(Box::new(Term::Fields(x))),
+            &Term::Object(Box::new(Term::Fields(ty_fields))),
+        )?;
+        Ok(Term::Obj(Box::new(Term::Fields(tm_fields))))
+    }
+
+    fn unstuck_find(&mut self, loc: Loc, tm: Term) -> Result<Term, Error> {
+        Ok(match tm {
+            Term::App(f, ai, x) => {
+                let f = self.unstuck_find(loc, *f)?;
+                let x = match *x {
+                    x @ Term::MetaRef(_, _, _) => Normalizer::new(&mut self.sigma, loc).term(x)?,
+                    x => x,
+                };
+                Normalizer::new(&mut self.sigma, loc).apply(f, ai, &[x])?
+            }
+            tm @ Term::Find(_, _, _) => Normalizer::new(&mut self.sigma, loc).term(tm)?,
+            tm => tm,
         })
     }
 
@@ -286,20 +959,29 @@ impl Elaborator {
         use MetaKind::*;
 
         Ok(match e {
-            Resolved(_, v) => match self.gamma.get(&v) {
+            Resolved(loc, v) => match self.gamma.get(&v) {
                 Some(ty) => (Term::Ref(v), *ty.clone()),
                 None => {
+                    if self.inferring.contains(&v) {
+                        return Err(UnannotatedRecursion(v, loc));
+                    }
                     let d = self.sigma.get(&v).unwrap();
                     (d.to_term(v), d.to_type())
                 }
             },
             Imported(_, v) => {
-                let ty = self.sigma.get(&v).unwrap().to_type();
-                (Term::Ref(v), ty)
+                let d = self.sigma.get(&v).unwrap();
+                match d.body {
+                    Body::Findable(_, _) | Body::Interface { .. } => (d.to_term(v), d.to_type()),
+                    _ => (Term::Ref(v), d.to_type()),
+                }
             }
             Qualified(_, m, v) => {
-                let ty = self.sigma.get(&v).unwrap().to_type();
-                (Term::Qualified(m, v), ty)
+                let d = self.sigma.get(&v).unwrap();
+                match d.body {
+                    Body::Findable(_, _) | Body::Interface { .. } => (d.to_term(v), d.to_type()),
+                    _ => (Term::Qualified(m, v), d.to_type()),
+                }
             }
             Hole(loc) => self.insert_meta(loc, UserMeta),
             InsertedHole(loc) => self.insert_meta(loc, InsertedMeta),
@@ -326,37 +1008,15 @@ impl Elaborator {
                     Term::Pi(param, Box::new(b_ty)),
                 )
             }
-            App(_, f, ai, x) => {
-                let f_loc = f.loc();
-                let f_e = f.clone();
-                let (f, f_ty) = self.infer(*f, hint)?;
-
-                if let Some(f_e) = Self::app_insert_holes(*f_e, ai.clone(), &f_ty)? {
-                    return self.infer(App(f_loc, Box::new(f_e), ai, x), hint);
-                }
-
-                match f_ty {
-                    Term::Pi(p, b) => {
-                        let x = self.guarded_check(
-                            &[&Param {
-                                var: p.var.clone(),
-                                info: p.info,
-                                typ: p.typ.clone(),
-                            }],
-                            *x,
-                            &p.typ,
-                        )?;
-                        let applied_ty =
-                            Normalizer::new(&mut self.sigma, f_loc).with(&[(&p.var, &x)], *b)?;
-                        let applied = Normalizer::new(&mut self.sigma, f_loc).apply(
-                            f,
-                            p.info.into(),
-                            &[x],
-                        )?;
-                        (applied, applied_ty)
-                    }
-                    ty => return Err(ExpectedPi(ty, f_loc)),
+            App(loc, f, ai, x) => {
+                if let Access(_, n) = f.as_ref() {
+                    let getter = Var::new(n.as_str()).getter();
+                    let n = n.clone();
+                    return self.member(loc, *x, &n, getter, TT(loc), hint, |s, o| {
+                        s.infer_app(f, ai, o, hint)
+                    });
                 }
+                self.infer_app(f, ai, *x, hint)?
             }
             Sigma(_, p, b) => {
                 let (param_ty, _) = self.infer(*p.typ, hint)?;
@@ -442,19 +1102,20 @@ impl Elaborator {
                 }
                 _ => unreachable!(),
             },
-            Concat(_, a, b) => {
-                let x_loc = a.loc();
-                let y_loc = b.loc();
-                let (x, x_ty) = self.infer(*a, hint)?;
-                let (y, y_ty) = self.infer(*b, hint)?;
-                let ty = match (x_ty, y_ty) {
-                    (Term::Object(rx), Term::Object(ry)) => {
-                        Box::new(Term::Object(Box::new(Term::Combine(rx, ry))))
+            Concat(loc, a, b) => {
+                if let Obj(_, r) = b.as_ref() {
+                    if let Fields(_, fields) = r.as_ref() {
+                        if let [(n, e)] = fields.as_slice() {
+                            let n = n.clone();
+                            let setter = Var::new(n.as_str()).setter();
+                            let arg = Tuple(e.loc(), Box::new(e.clone()), Box::new(TT(loc)));
+                            return self.member(loc, *a, &n, setter, arg, hint, |s, o| {
+                                s.infer_concat(o, *b, hint)
+                            });
+                        }
                     }
-                    (Term::Object(_), y_ty) => return Err(ExpectedObject(y_ty, y_loc)),
-                    (x_ty, _) => return Err(ExpectedObject(x_ty, x_loc)),
-                };
-                (Term::Concat(Box::new(x), Box::new(y)), *ty)
+                }
+                self.infer_concat(*a, *b, hint)?
             }
             Access(_, n) => {
                 let t = Var::new("T");
@@ -513,13 +1174,106 @@ impl Elaborator {
                     (ty, _) => return Err(ExpectedObject(ty, loc)),
                 }
             }
+            Restrict(loc, a, ns) => {
+                let (a, a_ty) = self.infer(*a, hint)?;
+                match Normalizer::new(&mut self.sigma, loc).term(a_ty)? {
+                    Term::Object(r) => {
+                        let mut r = *r;
+                        for n in &ns {
+                            r = Self::restrict_row(r.clone(), n).ok_or(UnresolvedField(
+                                n.clone(),
+                                Box::new(r),
+                                loc,
+                            ))?;
+                        }
+                        (Term::Restrict(Box::new(a), ns), Term::Object(Box::new(r)))
+                    }
+                    ty => return Err(ExpectedObject(ty, loc)),
+                }
+            }
+            MapMethod(loc, a, f) => {
+                let (a, a_ty) = self.infer(*a, hint)?;
+                let r = match Normalizer::new(&mut self.sigma, loc).term(a_ty)? {
+                    Term::Object(r) => r,
+                    ty => return Err(ExpectedObject(ty, loc)),
+                };
+                let (i, f, ret) = self.mappable_method(*f)?;
+                let tm = Term::MapMethod(Box::new(a), r.clone(), i, f);
+                (
+                    Normalizer::new(&mut self.sigma, loc).term(tm)?,
+                    Term::Object(Box::new(Term::MapFields(r, Box::new(ret)))),
+                )
+            }
+            Singleton(_, a) => (
+                Term::Singleton(Box::new(self.infer(*a, hint)?.0)),
+                Term::Univ,
+            ),
+            Union(_, ts) => {
+                let mut types = Vec::default();
+                for t in ts {
+                    types.push(self.check(t, &Term::Univ)?);
+                }
+                (Term::Union(types), Term::Univ)
+            }
+            Optional(_, a) => (
+                Term::Optional(Box::new(self.check(*a, &Term::Univ)?)),
+                Term::Univ,
+            ),
+            OptionalAccess(loc, a, n) => {
+                let (a, a_ty) = self.infer(*a, hint)?;
+                let r = match Normalizer::new(&mut self.sigma, loc).term(a_ty)? {
+                    Term::Object(r) => r,
+                    ty => return Err(ExpectedObject(ty, loc)),
+                };
+                if let Term::Fields(f) = r.as_ref() {
+                    self.check_private(loc, &n, f)?;
+                }
+                let ty = match Self::row_field(&r, &n) {
+                    Some(Term::Optional(t)) => *t,
+                    Some(t) => t,
+                    None => return Err(UnresolvedField(n, r, loc)),
+                };
+                let variants =
+                    FieldMap::from([("None".to_string(), Term::Unit), ("Some".to_string(), ty)]);
+                (
+                    Term::OptionalAccess(Box::new(a), n),
+                    Term::Enum(Box::new(Term::Fields(variants))),
+                )
+            }
+            Update(loc, a, f) => {
+                let (a, a_ty) = self.infer(*a, hint)?;
+                let r = match Normalizer::new(&mut self.sigma, loc).term(a_ty)? {
+                    Term::Object(r) => r,
+                    ty => return Err(ExpectedObject(ty, loc)),
+                };
+                let fields = match *f {
+                    Fields(_, fields) => fields,
+                    _ => unreachable!(),
+                };
+                let mut tm_fields = FieldMap::default();
+                for (n, e) in fields {
+                    if let Term::Fields(f) = r.as_ref() {
+                        self.check_private(loc, &n, f)?;
+                    }
+                    let ty = Self::row_field(&r, &n).ok_or(UnresolvedField(
+                        n.clone(),
+                        r.clone(),
+                        loc,
+                    ))?;
+                    tm_fields.insert(n, self.check(e, &ty)?);
+                }
+                (
+                    Term::Update(Box::new(a), Box::new(Term::Fields(tm_fields))),
+                    Term::Object(r),
+                )
+            }
             Enum(_, r) => {
                 let r = self.check(*r, &Term::Row)?;
                 (Term::Enum(Box::new(r)), Term::Univ)
             }
             Variant(loc, n, a) => {
-                let b_ty =
-                    Box::new(Normalizer::new(&mut self.sigma, loc).term(hint.unwrap().clone())?);
+                let hint_ty = Normalizer::new(&mut self.sigma, loc).term(hint.unwrap().clone())?;
+                let b_ty = Box::new(self.unfold_nominal(loc, hint_ty.clone())?);
                 let (a, a_ty) = self.infer(*a, hint)?;
                 match *b_ty {
                     Term::Enum(to) => match (a_ty, *to) {
@@ -528,7 +1282,10 @@ impl Elaborator {
                             Unifier::new(&mut self.sigma, loc).unify_fields_ord(&from, &to)?;
                             (
                                 Term::Variant(Box::new(Term::Fields(FieldMap::from([(n, a)])))),
-                                Term::Enum(Box::new(Term::Fields(to))),
+                                match hint_ty {
+                                    Term::Nominal(_, _) => hint_ty,
+                                    _ => Term::Enum(Box::new(Term::Fields(to))),
+                                },
                             )
                         }
                         (ty, _) => (
@@ -563,6 +1320,7 @@ impl Elaborator {
                 let a_loc = a.loc();
                 let (a, a_ty) = self.infer(*a, hint)?;
                 let en = Normalizer::new(&mut self.sigma, loc).term(a_ty)?;
+                let en = self.unfold_nominal(loc, en)?;
                 match en {
                     Term::Enum(y) => match *y {
                         Term::Fields(f) => {
@@ -573,7 +1331,7 @@ impl Elaborator {
                             for (n, v, e) in cs {
                                 let ty = f.get(&n).ok_or(UnresolvedField(
                                     n.clone(),
-                                    Term::Fields(f.clone()),
+                                    Box::new(Term::Fields(f.clone())),
                                     loc,
                                 ))?;
                                 let p = Param {
@@ -614,6 +1372,7 @@ impl Elaborator {
                     Term::Vptr(v, _) => v,
                     _ => unreachable!(),
                 };
+                self.check_private(loc, &n, &f)?;
                 let desugared = App(
                     loc,
                     Box::new(App(
@@ -644,10 +1403,10 @@ impl Elaborator {
                 }
                 (Term::Vptr(r, types), Term::Univ)
             }
-            Find(_, _, f) => {
-                let ty = self.sigma.get(&f).unwrap().to_type();
-                (Term::Ref(f), ty)
-            }
+            Find(loc, i, f) => match &self.sigma.get(&f).unwrap().body {
+                Body::Findable(j, _) if j == &i => self.infer(Resolved(loc, f), hint)?,
+                _ => return Err(UnresolvedField(f.to_string(), Box::new(Term::Ref(i)), loc)),
+            },
             ImplementsOf(loc, a) => {
                 let (tm, ty) = self.infer(*a, hint)?;
                 match tm {
@@ -656,6 +1415,13 @@ impl Elaborator {
                 }
             }
 
+            If(_, p, t, e) => {
+                let p = self.check(*p, &Term::Boolean)?;
+                let (t, ty) = self.infer(*t, hint)?;
+                let e = self.check(*e, &ty)?;
+                (Term::If(Box::new(p), Box::new(t), Box::new(e)), ty)
+            }
+
             Univ(_) => (Term::Univ, Term::Univ),
             Unit(_) => (Term::Unit, Term::Univ),
             TT(_) => (Term::TT, Term::Unit),
@@ -674,6 +1440,217 @@ impl Elaborator {
         })
     }
 
+    fn infer_app(
+        &mut self,
+        f: Box<Expr>,
+        ai: ArgInfo,
+        x: Expr,
+        hint: Option<&Term>,
+    ) -> Result<(Term, Term), Error> {
+        use Expr::*;
+
+        let f_loc = f.loc();
+        let f_e = f.clone();
+        let (f, f_ty) = self.infer(*f, hint)?;
+
+        if let Some(f_e) = Self::app_insert_holes(*f_e, ai.clone(), &f_ty)? {
+            return self.infer(App(f_loc, Box::new(f_e), ai, Box::new(x)), hint);
+        }
+
+        match f_ty {
+            Term::Pi(p, b) => {
+                let x = self.guarded_check(
+                    &[&Param {
+                        var: p.var.clone(),
+                        info: p.info,
+                        typ: p.typ.clone(),
+                    }],
+                    x,
+                    &p.typ,
+                )?;
+                let applied_ty =
+                    Normalizer::new(&mut self.sigma, f_loc).with(&[(&p.var, &x)], *b)?;
+                let applied =
+                    Normalizer::new(&mut self.sigma, f_loc).apply(f, p.info.into(), &[x])?;
+                Ok((applied, applied_ty))
+            }
+            ty => Err(ExpectedPi(ty, f_loc)),
+        }
+    }
+
+    fn infer_concat(
+        &mut self,
+        a: Expr,
+        b: Expr,
+        hint: Option<&Term>,
+    ) -> Result<(Term, Term), Error> {
+        let x_loc = a.loc();
+        let y_loc = b.loc();
+        let (x, x_ty) = self.infer(a, hint)?;
+        let (y, y_ty) = self.infer(b, hint)?;
+        let ty = match (x_ty, y_ty) {
+            (Term::Object(rx), Term::Object(ry)) => {
+                Box::new(Term::Object(Box::new(Term::Combine(rx, ry))))
+            }
+            (Term::Object(_), y_ty) => return Err(ExpectedObject(y_ty, y_loc)),
+            (x_ty, _) => return Err(ExpectedObject(x_ty, x_loc)),
+        };
+        Ok((Term::Concat(Box::new(x), Box::new(y)), *ty))
+    }
+
+    /// Member access on an object, which dispatches to the accessor of a class instance if the
+    /// member is not a field of it. The object is inferred only once and bound to a fresh variable
+    /// while elaborating the access.
+    #[allow(clippy::too_many_arguments)]
+    fn member(
+        &mut self,
+        loc: Loc,
+        o: Expr,
+        n: &str,
+        accessor: Var,
+        arg: Expr,
+        hint: Option<&Term>,
+        access: impl FnOnce(&mut Self, Expr) -> Result<(Term, Term), Error>,
+    ) -> Result<(Term, Term), Error> {
+        let o_loc = o.loc();
+        let (o, o_ty) = self.infer(o, hint)?;
+        let accessor = self.accessor(loc, n, &o_ty, accessor)?;
+        let x = Param {
+            var: self.vg.fresh(),
+            info: Explicit,
+            typ: Box::new(o_ty),
+        };
+        let r = Expr::Resolved(o_loc, x.var.clone());
+        self.gamma.insert(x.var.clone(), x.typ.clone());
+        let (tm, ty) = match accessor {
+            Some(a) => self.infer(Expr::Lookup(loc, Box::new(r), a, Box::new(arg)), hint)?,
+            None => access(self, r)?,
+        };
+        self.gamma.remove(&x.var);
+        let rho = [(&x.var, &o)];
+        let tm = Normalizer::new(&mut self.sigma, loc).with(&rho, tm)?;
+        let ty = Normalizer::new(&mut self.sigma, loc).with(&rho, ty)?;
+        Ok((tm, ty))
+    }
+
+    fn accessor(
+        &mut self,
+        loc: Loc,
+        n: &str,
+        o_ty: &Term,
+        accessor: Var,
+    ) -> Result<Option<String>, Error> {
+        let f = match o_ty {
+            Term::Object(f) => match f.as_ref() {
+                Term::Fields(f) if f.contains_key(VPTR) => f,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        self.check_private(loc, n, f)?;
+        if f.contains_key(n) {
+            return Ok(None);
+        }
+        let v = match f.get(VPTR) {
+            Some(Term::Vptr(v, _)) => v,
+            _ => unreachable!(),
+        };
+        let vtbl = *self.sigma.get(v).unwrap().ret.clone();
+        let has_accessor = match Normalizer::new(&mut self.sigma, loc).term(vtbl)? {
+            Term::Object(f) => matches!(*f, Term::Fields(f) if f.contains_key(accessor.as_str())),
+            _ => false,
+        };
+        match has_accessor {
+            true => Ok(Some(accessor.to_string())),
+            false => Err(UnresolvedField(n.to_string(), Box::new(o_ty.clone()), loc)),
+        }
+    }
+
+    fn check_private(&self, loc: Loc, n: &str, f: &FieldMap) -> Result<(), Error> {
+        if let Some(Term::Vptr(v, _)) = f.get(VPTR) {
+            let is_private = self
+                .privates
+                .get(v)
+                .is_some_and(|ns| ns.iter().any(|m| m == n));
+            if is_private && self.owner.as_ref() != Some(v) {
+                return Err(PrivateMember(n.to_string(), loc));
+            }
+        }
+        Ok(())
+    }
+
+    fn unfold_nominal(&mut self, loc: Loc, ty: Term) -> Result<Term, Error> {
+        let (v, ts) = match ty {
+            Term::Nominal(v, ts) => (v, ts),
+            ty => return Ok(ty),
+        };
+        let d = self.sigma.get(&v).unwrap();
+        let body = match &d.body {
+            Body::NominalEnum(t) => t.clone(),
+            _ => return Ok(Term::Nominal(v, ts)),
+        };
+        let tele = d.tele.clone();
+        let rho = tele
+            .iter()
+            .zip(ts.iter())
+            .map(|(p, t)| (&p.var, t))
+            .collect::<Vec<_>>();
+        Normalizer::new(&mut self.sigma, loc).with(&rho, body)
+    }
+
+    /// Checks that `f` is a method taking only a value of the single implementor of its interface,
+    /// returning the interface and the return type as a type function of the implementor.
+    fn mappable_method(&self, f: Expr) -> Result<(Var, Var, Term), Error> {
+        use Expr::*;
+
+        let (loc, v) = match f {
+            Resolved(loc, v) | Imported(loc, v) => (loc, v),
+            f => return Err(UnmappableMethod(f.to_string(), f.loc())),
+        };
+        let d = self.sigma.get(&v).unwrap();
+        if let (Body::Findable(i, 1), [t, p]) = (&d.body, d.tele.as_slice()) {
+            if let Term::Sigma(x, rest) = p.typ.as_ref() {
+                if matches!(x.typ.as_ref(), Term::Ref(r) if r == &t.var)
+                    && matches!(rest.as_ref(), Term::Unit)
+                {
+                    let ret = rename(Term::Lam(t.clone(), d.ret.clone()));
+                    return Ok((i.clone(), v, ret));
+                }
+            }
+        }
+        Err(UnmappableMethod(v.to_string(), loc))
+    }
+
+    fn row_field(r: &Term, n: &str) -> Option<Term> {
+        use Term::*;
+        match r {
+            Fields(f) => f.get(n).cloned(),
+            Combine(a, b) => Self::row_field(b, n).or_else(|| Self::row_field(a, n)),
+            _ => None,
+        }
+    }
+
+    fn restrict_row(r: Term, n: &str) -> Option<Term> {
+        use Term::*;
+        match r {
+            Fields(mut f) => f.remove(n).map(|_| Fields(f)),
+            Combine(a, b) => match Self::restrict_row(*b.clone(), n) {
+                Some(b) => Some(Self::combine_rows(*a, b)),
+                None => Self::restrict_row(*a, n).map(|a| Self::combine_rows(a, *b)),
+            },
+            _ => None,
+        }
+    }
+
+    fn combine_rows(a: Term, b: Term) -> Term {
+        use Term::*;
+        match (a, b) {
+            (Fields(f), b) if f.is_empty() => b,
+            (a, Fields(f)) if f.is_empty() => a,
+            (a, b) => Combine(Box::new(a), Box::new(b)),
+        }
+    }
+
     fn guarded_check(&mut self, ps: &[&Param<Term>], e: Expr, ty: &Term) -> Result<Term, Error> {
         for &p in ps {
             self.gamma.insert(p.var.clone(), p.typ.clone());
@@ -685,6 +1662,22 @@ impl Elaborator {
         Ok(ret)
     }
 
+    /// Checks a branch of a boolean test on `v`, with `v` substituted by `tm` in the expected type
+    /// and in the types of the context.
+    fn refined_check(&mut self, v: &Var, tm: Term, e: Expr, ty: &Term) -> Result<Term, Error> {
+        let loc = e.loc();
+        let rho = [(v, &tm)];
+        let saved = self.gamma.clone();
+        for (x, typ) in saved.iter() {
+            let typ = Normalizer::new(&mut self.sigma, loc).with(&rho, *typ.clone())?;
+            self.gamma.insert(x.clone(), Box::new(typ));
+        }
+        let ty = Normalizer::new(&mut self.sigma, loc).with(&rho, ty.clone())?;
+        let ret = self.check(e, &ty);
+        self.gamma = saved;
+        ret
+    }
+
     fn guarded_infer(
         &mut self,
         ps: &[&Param<Term>],
@@ -710,6 +1703,7 @@ impl Elaborator {
             Def {
                 loc,
                 name: ty_meta_var.clone(),
+                is_exported: false,
                 tele: Default::default(),
                 ret: Box::new(Term::Univ),
                 body: Meta(k.clone(), None),
@@ -725,6 +1719,7 @@ impl Elaborator {
             Def {
                 loc,
                 name: tm_meta_var.clone(),
+                is_exported: false,
                 tele,
                 ret: Box::new(ty.clone()),
                 body: Meta(k.clone(), None),
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 
@@ -119,26 +119,46 @@ impl Import {
 }
 
 #[derive(Default)]
-pub struct Loaded(HashMap<ModuleID, HashMap<String, Var>>);
+pub struct Loaded {
+    exported: HashMap<ModuleID, HashMap<String, Var>>,
+    unexported: HashMap<ModuleID, HashSet<String>>,
+    modules: HashMap<Var, ModuleID>,
+}
 
 impl Loaded {
     pub fn contains(&self, module: &ModuleID) -> bool {
-        self.0.contains_key(module)
+        self.exported.contains_key(module) || self.unexported.contains_key(module)
     }
 
     pub fn get(&self, module: &ModuleID, n: &String) -> Option<&Var> {
-        self.0.get(module).and_then(|m| m.get(n))
+        self.exported.get(module).and_then(|m| m.get(n))
+    }
+
+    pub fn module_of(&self, v: &Var) -> Option<&ModuleID> {
+        self.modules.get(v)
+    }
+
+    pub fn is_unexported(&self, module: &ModuleID, n: &String) -> bool {
+        self.unexported.get(module).is_some_and(|m| m.contains(n))
     }
 
     pub fn insert(&mut self, module: &ModuleID, def: &Def<Term>) -> Result<(), Error> {
-        match self.0.get_mut(module) {
+        if !def.is_exported {
+            self.unexported
+                .entry(module.clone())
+                .or_default()
+                .insert(def.name.to_string());
+            return Ok(());
+        }
+        self.modules.insert(def.name.clone(), module.clone());
+        match self.exported.get_mut(module) {
             Some(m) => {
                 if m.insert(def.name.to_string(), def.name.clone()).is_some() {
                     return Err(Error::DuplicateName(def.loc));
                 }
             }
             None => {
-                self.0.insert(
+                self.exported.insert(
                     module.clone(),
                     HashMap::from([(def.name.to_string(), def.name.clone())]),
                 );
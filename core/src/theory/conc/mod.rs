@@ -1,4 +1,5 @@
 pub mod data;
+pub mod derive;
 pub mod elab;
 pub mod load;
 pub mod resolve;
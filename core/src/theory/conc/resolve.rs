@@ -1,13 +1,15 @@
+use std::cmp::min;
 use std::collections::HashMap;
+use std::mem::take;
 
 use crate::theory::abs::def::Def;
 use crate::theory::abs::def::{Body, ImplementsBody};
-use crate::theory::conc::data::Expr;
 use crate::theory::conc::data::Expr::Unresolved;
-use crate::theory::conc::load::{Import, ImportedDefs, Loaded};
-use crate::theory::{Param, RawNameSet, Tele, Var, CTOR, UNBOUND};
-use crate::Error;
-use crate::Error::UnresolvedVar;
+use crate::theory::conc::data::{Derivation, Derived, Expr};
+use crate::theory::conc::load::{Import, ImportedDefs, Loaded, ModuleID};
+use crate::theory::{Loc, Param, RawNameSet, Tele, Var, CTOR, UNBOUND};
+use crate::Error::{CyclicAlias, OrphanImplementation, UnexportedVar, UnresolvedVar};
+use crate::{maybe_grow, Error};
 
 pub type NameMap = HashMap<String, ResolvedVar>;
 
@@ -23,15 +25,19 @@ pub struct ResolvedVar(pub VarKind, pub Var);
 pub struct Resolver<'a> {
     builtins: &'a NameMap,
     loaded: &'a Loaded,
+    module: Option<&'a ModuleID>,
     names: NameMap,
+    refs: Vec<Var>,
 }
 
 impl<'a> Resolver<'a> {
-    pub fn new(builtins: &'a NameMap, loaded: &'a Loaded) -> Self {
+    pub fn new(builtins: &'a NameMap, loaded: &'a Loaded, module: Option<&'a ModuleID>) -> Self {
         Self {
             builtins,
             loaded,
+            module,
             names: Default::default(),
+            refs: Default::default(),
         }
     }
 
@@ -39,7 +45,7 @@ impl<'a> Resolver<'a> {
         &mut self,
         imports: &mut Vec<Import>,
         defs: Vec<Def<Expr>>,
-    ) -> Result<Vec<Def<Expr>>, Error> {
+    ) -> Result<Vec<Vec<Def<Expr>>>, Error> {
         let mut names = RawNameSet::default();
         self.imports(&mut names, imports)?;
         self.defs(&mut names, defs)
@@ -63,7 +69,7 @@ impl<'a> Resolver<'a> {
 
                         match self.loaded.get(module, name) {
                             Some(v) => self.insert_imported(v),
-                            None => return Err(UnresolvedVar(*loc)),
+                            None => return Err(self.unresolved_in(module, name, *loc)),
                         };
                     }
                 }
@@ -78,15 +84,73 @@ impl<'a> Resolver<'a> {
         &mut self,
         names: &mut RawNameSet,
         defs: Vec<Def<Expr>>,
-    ) -> Result<Vec<Def<Expr>>, Error> {
+    ) -> Result<Vec<Vec<Def<Expr>>>, Error> {
+        // Functions and types are forward-declared, so they could be referenced before their
+        // definitions.
+        for d in &defs {
+            if matches!(
+                d.body,
+                Body::Fn(_)
+                    | Body::Class(_)
+                    | Body::Alias(_)
+                    | Body::NominalEnum(_)
+                    | Body::Newtype(_)
+            ) {
+                self.insert(&d.name);
+            }
+        }
+
+        let indices = defs
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (d.name.clone(), i))
+            .collect::<HashMap<_, _>>();
+        let aliases = defs
+            .iter()
+            .map(|d| matches!(d.body, Body::Alias(_)))
+            .collect::<Vec<_>>();
         let mut ret = Vec::default();
-        for d in defs {
+        let mut deps = Vec::default();
+        let mut alias_deps = Vec::default();
+        let mut prev = None;
+        for (i, d) in defs.into_iter().enumerate() {
             if d.name.as_str() != UNBOUND {
                 names.var(d.loc, &d.name)?;
             }
-            ret.push(self.def(d)?);
+            let is_fn = matches!(d.body, Body::Fn(_));
+            ret.push(Some(self.def(d)?));
+
+            // Other definitions (e.g. implementations) might be depended on implicitly, so they
+            // always stay in order.
+            let mut ds = take(&mut self.refs)
+                .iter()
+                .filter_map(|v| indices.get(v).cloned())
+                .collect::<Vec<_>>();
+            alias_deps.push(match aliases[i] {
+                true => ds.iter().filter(|&&j| aliases[j]).cloned().collect(),
+                false => Vec::default(),
+            });
+            ds.extend(prev);
+            if !is_fn {
+                prev = Some(i);
+            }
+            deps.push(ds);
         }
-        Ok(ret)
+
+        // Aliases are unfolded while checking, so they must not refer to themselves.
+        for scc in Components::new(&alias_deps).sccs() {
+            let i = scc[0];
+            if scc.len() > 1 || alias_deps[i].contains(&i) {
+                let d = ret[*scc.iter().min().unwrap()].as_ref().unwrap();
+                return Err(CyclicAlias(d.name.clone(), d.loc));
+            }
+        }
+
+        Ok(Components::new(&deps)
+            .sccs()
+            .into_iter()
+            .map(|scc| scc.into_iter().map(|i| ret[i].take().unwrap()).collect())
+            .collect())
     }
 
     fn def(&mut self, mut d: Def<Expr>) -> Result<Def<Expr>, Error> {
@@ -114,6 +178,10 @@ impl<'a> Resolver<'a> {
             Fn(f) => Fn(self.self_referencing_fn(&d.name, f)?),
             Postulate => Postulate,
             Alias(t) => Alias(self.expr(t)?),
+            NominalEnum(t) => NominalEnum(self.self_referencing_fn(&d.name, t)?),
+            Newtype(t) => Newtype(self.expr(t)?),
+            Coerce => Coerce,
+            StaticAssert(a, b) => StaticAssert(self.expr(a)?, self.expr(b)?),
             Const(anno, f) => Const(anno, self.expr(f)?),
 
             Class(mut body) => {
@@ -127,25 +195,57 @@ impl<'a> Resolver<'a> {
             VtblType(t) => VtblType(self.expr(t)?),
             VtblLookup => VtblLookup,
 
-            Interface { fns, ims } => Interface { fns, ims },
+            Interface {
+                fns,
+                ims,
+                defaults,
+                supers,
+            } => {
+                let mut resolved = Vec::default();
+                for s in supers {
+                    resolved.push(match self.expr(Unresolved(d.loc, None, s))? {
+                        Expr::Resolved(_, s) | Expr::Imported(_, s) => s,
+                        _ => unreachable!(),
+                    });
+                }
+                Interface {
+                    fns,
+                    ims,
+                    defaults,
+                    supers: resolved,
+                }
+            }
             Implements(body) => {
                 let loc = d.loc;
-                let i = self.expr(Unresolved(loc, None, body.i.0))?.resolved();
-                let im = self.expr(*body.i.1)?;
+                let i = match self.expr(Unresolved(loc, None, body.i.0))? {
+                    Expr::Resolved(_, i) | Expr::Imported(_, i) => i,
+                    _ => unreachable!(),
+                };
+                let mut ims = Vec::default();
+                for im in body.i.1 {
+                    ims.push(self.expr(im)?);
+                }
+                self.check_orphan(&i, &ims, loc)?;
+                let i_module = self.loaded.module_of(&i);
                 let mut fns = HashMap::default();
                 for (i_fn, im_fn) in body.fns {
-                    fns.insert(
-                        self.expr(Unresolved(loc, None, i_fn))?.resolved(),
-                        self.expr(Unresolved(loc, None, im_fn))?.resolved(),
-                    );
+                    let i_fn = match i_module {
+                        Some(m) => match self.loaded.get(m, &i_fn.to_string()) {
+                            Some(v) => v.clone(),
+                            None => return Err(self.unresolved_in(m, &i_fn.to_string(), loc)),
+                        },
+                        None => self.expr(Unresolved(loc, None, i_fn))?.resolved(),
+                    };
+                    fns.insert(i_fn, self.expr(Unresolved(loc, None, im_fn))?.resolved());
                 }
                 Implements(Box::new(ImplementsBody {
-                    i: (i, Box::new(im)),
+                    i: (i, ims),
                     fns,
+                    supers: Default::default(),
                 }))
             }
             ImplementsFn(f) => ImplementsFn(self.expr(f)?), // FIXME: currently cannot be recursive
-            Findable(i) => Findable(i),
+            Findable(i, n) => Findable(i, n),
 
             Undefined => unreachable!(),
             Meta(_, _) => unreachable!(),
@@ -218,14 +318,17 @@ impl<'a> Resolver<'a> {
             Unresolved(loc, m, r) => match m {
                 Some(m) => match self.loaded.get(&m, &r.to_string()) {
                     Some(r) => Qualified(loc, m, r.clone()),
-                    None => return Err(UnresolvedVar(loc)),
+                    None => return Err(self.unresolved_in(&m, &r.to_string(), loc)),
                 },
                 None => match self.get(&r) {
                     Some(v) => {
                         let k = v.0;
                         let v = v.1.clone();
                         match k {
-                            VarKind::InModule => Resolved(loc, v),
+                            VarKind::InModule => {
+                                self.refs.push(v.clone());
+                                Resolved(loc, v)
+                            }
                             VarKind::Imported => Imported(loc, v),
                         }
                     }
@@ -309,6 +412,36 @@ impl<'a> Resolver<'a> {
             Obj(loc, a) => Obj(loc, Box::new(self.expr(*a)?)),
             Concat(loc, a, b) => Concat(loc, Box::new(self.expr(*a)?), Box::new(self.expr(*b)?)),
             Downcast(loc, a) => Downcast(loc, Box::new(self.expr(*a)?)),
+            Restrict(loc, a, ns) => Restrict(loc, Box::new(self.expr(*a)?), ns),
+            MapMethod(loc, a, f) => {
+                MapMethod(loc, Box::new(self.expr(*a)?), Box::new(self.expr(*f)?))
+            }
+            ObjectLet(loc, fs, rest, a, b) => {
+                let mut vars = fs.iter().map(|(_, v)| v).collect::<Vec<_>>();
+                vars.extend(rest.iter());
+                let b = Box::new(self.bodied(&vars, *b)?);
+                ObjectLet(loc, fs, rest, Box::new(self.expr(*a)?), b)
+            }
+            LocalFn(loc, d, b) => {
+                let old = self.get(&d.name).cloned();
+                let d = self.def(*d)?;
+                let b = self.expr(*b)?;
+                match old {
+                    Some(v) => self.insert_resolved(v),
+                    None => self.remove(&d.name),
+                }
+                LocalFn(loc, Box::new(d), Box::new(b))
+            }
+            Optional(loc, a) => Optional(loc, Box::new(self.expr(*a)?)),
+            Singleton(loc, a) => Singleton(loc, Box::new(self.expr(*a)?)),
+            Union(loc, ts) => Union(
+                loc,
+                ts.into_iter()
+                    .map(|t| self.expr(t))
+                    .collect::<Result<_, _>>()?,
+            ),
+            OptionalAccess(loc, a, n) => OptionalAccess(loc, Box::new(self.expr(*a)?), n),
+            Update(loc, a, f) => Update(loc, Box::new(self.expr(*a)?), Box::new(self.expr(*f)?)),
             Enum(loc, a) => Enum(loc, Box::new(self.expr(*a)?)),
             Variant(loc, n, a) => Variant(loc, n, Box::new(self.expr(*a)?)),
             Upcast(loc, a) => Upcast(loc, Box::new(self.expr(*a)?)),
@@ -334,13 +467,161 @@ impl<'a> Resolver<'a> {
             }
             Constraint(loc, r) => Constraint(loc, Box::new(self.expr(*r)?)),
             ImplementsOf(loc, a) => ImplementsOf(loc, Box::new(self.expr(*a)?)),
+            Find(loc, i, f) => Find(
+                loc,
+                self.expr(Unresolved(loc, None, i))?.resolved(),
+                self.expr(Unresolved(loc, None, f))?.resolved(),
+            ),
+            Derive(loc, d) => Derive(loc, Box::new(self.derivation(loc, *d)?)),
 
             e => e,
         })
     }
 
+    fn derivation(&mut self, loc: Loc, d: Derivation) -> Result<Derivation, Error> {
+        let shape = match d.shape {
+            Derived::Object(fs) => Derived::Object(self.derived_fields(fs)?),
+            Derived::Enum(vs) => Derived::Enum(self.derived_fields(vs)?),
+            Derived::Class(name, ctor, fs) => {
+                Derived::Class(name, Box::new(self.expr(*ctor)?), self.derived_fields(fs)?)
+            }
+        };
+        let i = match self.expr(Unresolved(loc, None, d.i))? {
+            Expr::Resolved(_, i) | Expr::Imported(_, i) => i,
+            _ => unreachable!(),
+        };
+        Ok(Derivation {
+            implements: d.implements,
+            i,
+            typ: self.expr(d.typ)?,
+            shape,
+            concat: self.expr(d.concat)?,
+            quote: self.expr(d.quote)?,
+        })
+    }
+
+    fn derived_fields(&mut self, fs: Vec<(String, Expr)>) -> Result<Vec<(String, Expr)>, Error> {
+        let mut resolved = Vec::default();
+        for (n, t) in fs {
+            resolved.push((n, self.expr(t)?));
+        }
+        Ok(resolved)
+    }
+
+    /// Either the interface or one of the implementor heads must be defined in the current package.
+    fn check_orphan(&self, i: &Var, ims: &[Expr], loc: Loc) -> Result<(), Error> {
+        use Expr::*;
+
+        let m = match self.module {
+            Some(m) => m,
+            None => return Ok(()),
+        };
+        if self.is_local(m, i) {
+            return Ok(());
+        }
+        for im in ims {
+            let mut head = im;
+            while let App(_, f, _, _) = head {
+                head = f;
+            }
+            if let Resolved(_, v) | Imported(_, v) | Qualified(_, _, v) = head {
+                if self.is_local(m, v) {
+                    return Ok(());
+                }
+            }
+        }
+        Err(OrphanImplementation(i.to_string(), loc))
+    }
+
+    fn is_local(&self, m: &ModuleID, v: &Var) -> bool {
+        let is_builtin = self.builtins.get(v.as_str()).is_some_and(|b| b.1 == *v);
+        !is_builtin && self.loaded.module_of(v).is_none_or(|vm| vm.pkg == m.pkg)
+    }
+
+    fn unresolved_in(&self, module: &ModuleID, n: &String, loc: Loc) -> Error {
+        if self.loaded.is_unexported(module, n) {
+            UnexportedVar(n.clone(), loc)
+        } else {
+            UnresolvedVar(loc)
+        }
+    }
+
     fn self_referencing_fn(&mut self, name: &Var, f: Expr) -> Result<Expr, Error> {
         self.insert(name);
         self.expr(f)
     }
 }
+
+/// Strongly-connected components of the definitions in a module, by Tarjan's algorithm. Components
+/// come after the ones they depend on, and definitions stay in source order whenever possible.
+struct Components<'a> {
+    deps: &'a [Vec<usize>],
+    indices: Vec<Option<usize>>,
+    lows: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next: usize,
+    sccs: Vec<Vec<usize>>,
+}
+
+impl<'a> Components<'a> {
+    fn new(deps: &'a [Vec<usize>]) -> Self {
+        Self {
+            deps,
+            indices: vec![None; deps.len()],
+            lows: vec![0; deps.len()],
+            on_stack: vec![false; deps.len()],
+            stack: Default::default(),
+            next: Default::default(),
+            sccs: Default::default(),
+        }
+    }
+
+    fn sccs(mut self) -> Vec<Vec<usize>> {
+        for v in 0..self.deps.len() {
+            if self.indices[v].is_none() {
+                self.visit(v);
+            }
+        }
+        self.sccs
+    }
+
+    fn visit(&mut self, v: usize) {
+        maybe_grow(move || self.visit_impl(v))
+    }
+
+    fn visit_impl(&mut self, v: usize) {
+        self.indices[v] = Some(self.next);
+        self.lows[v] = self.next;
+        self.next += 1;
+        self.stack.push(v);
+        self.on_stack[v] = true;
+
+        let deps = self.deps;
+        for &w in &deps[v] {
+            match self.indices[w] {
+                None => {
+                    self.visit(w);
+                    self.lows[v] = min(self.lows[v], self.lows[w]);
+                }
+                Some(i) if self.on_stack[w] => self.lows[v] = min(self.lows[v], i),
+                _ => {}
+            }
+        }
+
+        if Some(self.lows[v]) != self.indices[v] {
+            return;
+        }
+        let mut scc = Vec::default();
+        loop {
+            let w = self.stack.pop().unwrap();
+            self.on_stack[w] = false;
+            scc.push(w);
+            if w == v {
+                break;
+            }
+        }
+        scc.sort();
+        self.sccs.push(scc);
+    }
+}
@@ -8,7 +8,7 @@ use crate::theory::abs::data::Dir;
 use crate::theory::abs::def::Def;
 use crate::theory::abs::def::{Body, ClassBody, ImplementsBody};
 use crate::theory::conc::data::ArgInfo::{NamedImplicit, UnnamedExplicit, UnnamedImplicit};
-use crate::theory::conc::data::{ArgInfo, Expr};
+use crate::theory::conc::data::{ArgInfo, Derivation, Derived, Expr};
 use crate::theory::conc::load::ImportedPkg::Vendor;
 use crate::theory::conc::load::{Import, ImportedDefs, ImportedPkg, ModuleID};
 use crate::theory::ParamInfo::{Explicit, Implicit};
@@ -38,21 +38,38 @@ impl Trans {
                 Rule::import_std | Rule::import_vendor | Rule::import_local => {
                     imports.push(self.import(d))
                 }
-                Rule::fn_def => defs.push(self.fn_def(d, None)),
-                Rule::fn_postulate => defs.push(self.fn_postulate(d)),
-                Rule::type_postulate => defs.push(self.type_postulate(d)),
-                Rule::type_alias => defs.push(self.type_alias(d)),
-                Rule::class_def => defs.extend(self.class_def(d)),
-                Rule::interface_def => defs.extend(self.interface_def(d)),
-                Rule::implements_def => defs.extend(self.implements_def(d)),
-                Rule::const_def => defs.push(self.const_def(d)),
+                Rule::exported_def => {
+                    defs.extend(self.def(d.into_inner().next().unwrap()).into_iter().map(
+                        |mut d| {
+                            d.is_exported = true;
+                            d
+                        },
+                    ))
+                }
                 Rule::EOI => break,
-                _ => unreachable!(),
+                _ => defs.extend(self.def(d)),
             }
         }
         (imports, defs)
     }
 
+    fn def(&self, d: Pair<Rule>) -> Vec<Def<Expr>> {
+        match d.as_rule() {
+            Rule::fn_def => vec![self.fn_def(d, None)],
+            Rule::fn_postulate => vec![self.fn_postulate(d)],
+            Rule::type_postulate => vec![self.type_postulate(d)],
+            Rule::type_alias => self.type_alias(d),
+            Rule::enum_def => vec![self.enum_def(d)],
+            Rule::newtype_def => self.newtype_def(d),
+            Rule::class_def => self.class_def(d),
+            Rule::interface_def => self.interface_def(d),
+            Rule::implements_def => self.implements_def(d),
+            Rule::static_assert => vec![self.static_assert(d)],
+            Rule::const_def => vec![self.const_def(d)],
+            _ => unreachable!(),
+        }
+    }
+
     fn import(&self, d: Pair<Rule>) -> Import {
         use ImportedDefs::*;
         use ImportedPkg::*;
@@ -120,12 +137,19 @@ impl Trans {
         let loc = Loc::from(f.as_span());
         let mut pairs = f.into_inner();
 
-        let name = Var::from(pairs.next().unwrap());
+        // Class methods may come with the `private` modifier, which is collected by the class.
+        let name = Var::from(pairs.find(|p| p.as_rule() != Rule::class_private).unwrap());
 
         let mut tele = Tele::default();
         let mut untupled = UntupledParams::new(loc);
         let mut preds = Tele::default();
-        let mut ret = Box::new(Unit(loc));
+        // Methods default to the unit type, since their types are part of the vtable type.
+        let mut ret = Box::new(if this.is_some() {
+            Unit(loc)
+        } else {
+            InsertedHole(loc)
+        });
+        let mut is_dependent = false;
         let mut body = None;
 
         if let Some((ty, implicits)) = this {
@@ -146,7 +170,10 @@ impl Trans {
                 Rule::implicit_id => tele.push(Self::implicit_param(p)),
                 Rule::hkt_param => tele.push(Self::hkt_param(p)),
                 Rule::param => untupled.push(Loc::from(p.as_span()), self.param(p)),
-                Rule::type_expr => ret = Box::new(self.type_expr(p)),
+                Rule::type_expr => {
+                    is_dependent = untupled.is_mentioned(&p);
+                    ret = Box::new(self.type_expr(p))
+                }
                 Rule::fn_body => {
                     body = Some(self.fn_body(p));
                     break;
@@ -158,6 +185,14 @@ impl Trans {
         let untupled_vars = untupled.unresolved();
         let untupled_loc = untupled.0;
         let tupled_param = Param::from(untupled);
+        if is_dependent {
+            ret = Box::new(Expr::wrap_tuple_lets(
+                untupled_loc,
+                &tupled_param.var,
+                untupled_vars.clone(),
+                *ret,
+            ));
+        }
         let body = Fn(Expr::wrap_tuple_lets(
             untupled_loc,
             &tupled_param.var,
@@ -170,6 +205,7 @@ impl Trans {
         Def {
             loc,
             name,
+            is_exported: false,
             tele,
             ret,
             body,
@@ -183,7 +219,8 @@ impl Trans {
         let loc = Loc::from(f.as_span());
         let mut pairs = f.into_inner();
 
-        let name = Var::from(pairs.next().unwrap());
+        // Class methods may come with the `private` modifier, which is collected by the class.
+        let name = Var::from(pairs.find(|p| p.as_rule() != Rule::class_private).unwrap());
 
         let mut tele = Tele::default();
         let mut untupled = UntupledParams::new(loc);
@@ -191,9 +228,11 @@ impl Trans {
 
         for p in pairs {
             match p.as_rule() {
+                Rule::row_id => tele.push(Self::row_param(p)),
                 Rule::implicit_id => tele.push(Self::implicit_param(p)),
                 Rule::param => untupled.push(Loc::from(p.as_span()), self.param(p)),
                 Rule::type_expr => ret = Box::new(self.type_expr(p)),
+                Rule::fn_body => break,
                 _ => unreachable!(),
             }
         }
@@ -202,6 +241,7 @@ impl Trans {
         Def {
             loc,
             name,
+            is_exported: false,
             tele,
             ret,
             body: Postulate,
@@ -219,13 +259,134 @@ impl Trans {
         Def {
             loc,
             name,
+            is_exported: false,
             tele: Default::default(),
             ret,
             body: Postulate,
         }
     }
 
-    fn type_alias(&self, t: Pair<Rule>) -> Def<Expr> {
+    fn enum_def(&self, e: Pair<Rule>) -> Def<Expr> {
+        use Body::*;
+        use Expr::*;
+
+        let loc = Loc::from(e.as_span());
+        let mut pairs = e.into_inner();
+
+        let name = Var::from(pairs.next().unwrap());
+        let mut tele = Tele::default();
+        let mut target = None;
+        for p in pairs {
+            match p.as_rule() {
+                Rule::row_id => tele.push(Self::row_param(p)),
+                Rule::implicit_id => tele.push(Self::implicit_param(p)),
+                Rule::enum_type_literal => {
+                    let p_loc = Loc::from(p.as_span());
+                    target = Some(Enum(p_loc, Box::new(self.fields(p))))
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        Def {
+            loc,
+            name,
+            is_exported: false,
+            tele,
+            ret: Box::new(Univ(loc)),
+            body: NominalEnum(target.unwrap()),
+        }
+    }
+
+    fn newtype_def(&self, n: Pair<Rule>) -> Vec<Def<Expr>> {
+        use Body::*;
+        use Expr::*;
+
+        let loc = Loc::from(n.as_span());
+        let mut pairs = n.into_inner();
+
+        let name = Var::from(pairs.next().unwrap());
+        let mut tele = Tele::default();
+        let mut target = None;
+        let mut interfaces = Vec::default();
+        for p in pairs {
+            match p.as_rule() {
+                Rule::row_id => tele.push(Self::row_param(p)),
+                Rule::implicit_id => tele.push(Self::implicit_param(p)),
+                Rule::type_expr => target = Some(self.type_expr(p)),
+                Rule::newtype_implements => interfaces.extend(p.into_inner().map(Var::from)),
+                _ => unreachable!(),
+            }
+        }
+        let target = target.unwrap();
+        let newtype = Self::wrap_implicit_apps(&tele, Unresolved(loc, None, name.clone()));
+
+        // Both coercions are the identity at runtime, so they are inlined and never emitted.
+        let coerce_def = |f: &str, from: Expr, to: Expr| {
+            let mut coerce_tele = tele.clone();
+            coerce_tele.push(Param {
+                var: Var::tupled(),
+                info: Explicit,
+                typ: Box::new(Sigma(
+                    loc,
+                    Param {
+                        var: Var::new("a"),
+                        info: Explicit,
+                        typ: Box::new(from),
+                    },
+                    Box::new(Unit(loc)),
+                )),
+            });
+            Def {
+                loc,
+                name: name.static_member(Var::new(f)),
+                is_exported: false,
+                tele: coerce_tele,
+                ret: Box::new(to),
+                body: Coerce,
+            }
+        };
+        let wrap_def = coerce_def("wrap", target.clone(), newtype.clone());
+        let unwrap_def = coerce_def("unwrap", newtype.clone(), target.clone());
+
+        // Forwarding implementations call the functions implemented for the underlying type, which
+        // are generated while elaborating.
+        let implements_defs = interfaces
+            .into_iter()
+            .map(|i| {
+                let ims = vec![newtype.clone()];
+                Def {
+                    loc,
+                    name: i.implements(&ims),
+                    is_exported: false,
+                    tele: Self::implements_tele(loc, &tele, &i),
+                    ret: Box::new(Univ(loc)),
+                    body: Implements(Box::new(ImplementsBody {
+                        i: (i, ims),
+                        fns: Default::default(),
+                        supers: Default::default(),
+                    })),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut defs = vec![
+            Def {
+                loc,
+                name,
+                is_exported: false,
+                tele,
+                ret: Box::new(Univ(loc)),
+                body: Newtype(target),
+            },
+            wrap_def,
+            unwrap_def,
+        ];
+        defs.extend(implements_defs);
+        defs
+    }
+
+    fn type_alias(&self, t: Pair<Rule>) -> Vec<Def<Expr>> {
         use Body::*;
         use Expr::*;
 
@@ -234,23 +395,162 @@ impl Trans {
 
         let name = Var::from(pairs.next().unwrap());
         let mut tele = Tele::default();
+        let mut untupled = UntupledParams::new(loc);
         let mut target = None;
+        let mut derived_defs = Vec::default();
         for p in pairs {
             match p.as_rule() {
                 Rule::row_id => tele.push(Self::row_param(p)),
                 Rule::implicit_id => tele.push(Self::implicit_param(p)),
+                Rule::param => untupled.push(Loc::from(p.as_span()), self.param(p)),
                 Rule::type_expr => target = Some(self.type_expr(p)),
+                Rule::derived_type => {
+                    let mut pairs = p.into_inner();
+                    let t = pairs.next().unwrap();
+                    let t_loc = Loc::from(t.as_span());
+                    let rule = t.as_rule();
+                    let fields = match self.fields(t) {
+                        Fields(_, fields) => fields,
+                        _ => unreachable!(),
+                    };
+                    let interfaces = Self::derive(pairs.next().unwrap());
+                    let derived = match rule {
+                        Rule::object_type_literal => Derived::Object(fields.clone()),
+                        Rule::enum_type_literal => Derived::Enum(fields.clone()),
+                        _ => unreachable!(),
+                    };
+                    derived_defs = Self::derived_defs(loc, &name, &tele, derived, interfaces);
+                    let fields = Box::new(Fields(t_loc, fields));
+                    target = Some(match rule {
+                        Rule::object_type_literal => Object(t_loc, fields),
+                        _ => Enum(t_loc, fields),
+                    });
+                }
                 _ => unreachable!(),
             }
         }
 
-        Def {
+        let mut target = target.unwrap();
+
+        // Type aliases with parameters are type-level functions, applied like ordinary functions.
+        if !untupled.1.is_empty() {
+            let untupled_vars = untupled.unresolved();
+            let untupled_loc = untupled.0;
+            let tupled_param = Param::from(untupled);
+            target = Expr::wrap_tuple_lets(untupled_loc, &tupled_param.var, untupled_vars, target);
+            tele.push(tupled_param);
+        }
+
+        let mut defs = vec![Def {
             loc,
             name,
+            is_exported: false,
             tele,
             ret: Box::new(Univ(loc)),
-            body: Alias(target.unwrap()),
+            body: Alias(target),
+        }];
+        defs.extend(derived_defs);
+        defs
+    }
+
+    fn static_assert(&self, a: Pair<Rule>) -> Def<Expr> {
+        use Body::*;
+        use Expr::*;
+
+        let loc = Loc::from(a.as_span());
+        let mut pairs = a.into_inner();
+        let lhs = self.type_expr(pairs.next().unwrap());
+        let rhs = self.type_expr(pairs.next().unwrap());
+
+        Def {
+            loc,
+            name: Var::unbound(),
+            is_exported: false,
+            tele: Default::default(),
+            ret: Box::new(Unit(loc)),
+            body: StaticAssert(lhs, rhs),
+        }
+    }
+
+    fn derive(d: Pair<Rule>) -> Vec<Var> {
+        d.into_inner().map(Var::from).collect()
+    }
+
+    /// The type parameters of an implementation for a generic type, each of them constrained to
+    /// implement the interface as well.
+    fn implements_tele(loc: Loc, tele: &Tele<Expr>, i: &Var) -> Tele<Expr> {
+        use Expr::*;
+
+        let mut im_tele = tele.clone();
+        for p in tele {
+            if !matches!(p.typ.as_ref(), Univ(_)) {
+                continue;
+            }
+            let constraint = App(
+                loc,
+                Box::new(Unresolved(loc, None, i.clone())),
+                UnnamedImplicit,
+                Box::new(Unresolved(loc, None, p.var.clone())),
+            );
+            im_tele.push(Param {
+                var: Var::unbound(),
+                info: Implicit,
+                typ: Box::new(ImplementsOf(loc, Box::new(constraint))),
+            });
+        }
+        im_tele
+    }
+
+    /// Generates the implementations of the derived interfaces, whose functions are generated from
+    /// the shape of the type while elaborating, once the signatures of the interfaces are known.
+    fn derived_defs(
+        loc: Loc,
+        name: &Var,
+        tele: &Tele<Expr>,
+        shape: Derived,
+        interfaces: Vec<Var>,
+    ) -> Vec<Def<Expr>> {
+        use Body::*;
+        use Expr::*;
+
+        let typ = Self::wrap_implicit_apps(tele, Unresolved(loc, None, name.clone()));
+
+        let mut defs = Vec::default();
+        for i in interfaces {
+            let ims = vec![typ.clone()];
+
+            let im_tele = Self::implements_tele(loc, tele, &i);
+            let implements = i.implements(&ims);
+            let derivation = Derivation {
+                implements: implements.clone(),
+                i: i.clone(),
+                typ: typ.clone(),
+                shape: shape.clone(),
+                concat: Unresolved(loc, None, Var::new("string#__add__")),
+                quote: Unresolved(loc, None, Var::new("string#__quote__")),
+            };
+            defs.push(Def {
+                loc,
+                name: Var::new("derive").implement_func(&i, &ims),
+                is_exported: false,
+                tele: im_tele.clone(),
+                ret: Box::new(typ.clone()),
+                body: ImplementsFn(Derive(loc, Box::new(derivation))),
+            });
+            defs.push(Def {
+                loc,
+                name: implements,
+                is_exported: false,
+                tele: im_tele,
+                ret: Box::new(Univ(loc)),
+                body: Implements(Box::new(ImplementsBody {
+                    i: (i, ims),
+                    fns: Default::default(),
+                    supers: Default::default(),
+                })),
+            });
         }
+        defs
     }
 
     fn wrap_implicit_apps(implicits: &Tele<Expr>, mut e: Expr) -> Expr {
@@ -285,26 +585,64 @@ impl Trans {
         let mut members = Vec::default();
         let mut method_defs = Vec::default();
         let mut methods = Vec::default();
+        let mut statics = Vec::default();
+        let mut static_defs = Vec::default();
+        let mut privates = Vec::default();
+        let mut interfaces = Vec::default();
+        let mut derived_interfaces = Vec::default();
 
         let mut vtbl_fields = Vec::default();
         let mut init_expr = None;
         for p in pairs {
             match p.as_rule() {
                 Rule::implicit_id => tele.push(Self::implicit_param(p)),
+                Rule::class_implements => interfaces.extend(p.into_inner().map(Var::from)),
+                Rule::derive => derived_interfaces = Self::derive(p),
                 Rule::class_member => {
                     let loc = Loc::from(p.as_span());
-                    members.push((loc, self.param(p)));
+                    let mut pairs = p.into_inner();
+                    let mut id = pairs.next().unwrap();
+                    if id.as_rule() == Rule::class_private {
+                        id = pairs.next().unwrap();
+                        privates.push(id.as_str().to_string());
+                    }
+                    let m = Param {
+                        var: Var::from(id),
+                        info: Explicit,
+                        typ: Box::new(self.type_expr(pairs.next().unwrap())),
+                    };
+                    members.push((loc, m));
                 }
                 Rule::class_init => {
                     let loc = Loc::from(p.as_span());
                     init_expr = Some((loc, self.fn_body(p.into_inner().next().unwrap())));
                 }
-                Rule::class_method => {
+                Rule::class_method | Rule::class_getter | Rule::class_setter => {
+                    let rule = p.as_rule();
+                    let is_private = p
+                        .clone()
+                        .into_inner()
+                        .next()
+                        .is_some_and(|p| p.as_rule() == Rule::class_private);
                     let mut m =
                         self.fn_def(p, Some((Unresolved(loc, None, name.clone()), tele.clone())));
+                    m.name = match rule {
+                        Rule::class_getter => m.name.getter(),
+                        Rule::class_setter => {
+                            m.ret = Box::new(Self::wrap_implicit_apps(
+                                &tele,
+                                Unresolved(loc, None, name.clone()),
+                            ));
+                            m.name.setter()
+                        }
+                        _ => m.name,
+                    };
                     vtbl_fields.push((m.name.to_string(), m.to_type()));
 
                     let meth_name = m.name.to_string();
+                    if is_private {
+                        privates.push(meth_name.clone());
+                    }
                     let fn_name = name.method(m.name);
                     m.name = fn_name.clone();
 
@@ -316,13 +654,52 @@ impl Trans {
                     methods.push((meth_name, fn_name));
                     method_defs.push(m);
                 }
+                Rule::class_static_method => {
+                    let mut f = self.fn_def(p, None);
+                    let mut f_tele = tele.clone();
+                    f_tele.extend(f.tele);
+                    f.tele = f_tele;
+                    f.name = name.static_member(f.name);
+                    statics.push(f.name.clone());
+                    static_defs.push(f);
+                }
+                Rule::class_static_const => {
+                    let mut c = self.const_def(p);
+                    c.name = name.static_member(c.name);
+                    statics.push(c.name.clone());
+                    static_defs.push(c);
+                }
                 _ => unreachable!(),
             }
         }
 
+        // Methods of the class are looked up as the implementation functions while elaborating.
+        let implements_defs = interfaces
+            .into_iter()
+            .map(|i| {
+                let ims = vec![Self::wrap_implicit_apps(
+                    &tele,
+                    Unresolved(loc, None, name.clone()),
+                )];
+                Def {
+                    loc,
+                    name: i.implements(&ims),
+                    is_exported: false,
+                    tele: tele.clone(),
+                    ret: Box::new(Univ(loc)),
+                    body: Implements(Box::new(ImplementsBody {
+                        i: (i, ims),
+                        fns: Default::default(),
+                        supers: Default::default(),
+                    })),
+                }
+            })
+            .collect::<Vec<_>>();
+
         let vptr_def = Def {
             loc,
             name: vptr_name.clone(),
+            is_exported: false,
             tele: tele.clone(),
             ret: Box::new(Univ(loc)),
             body: VptrType(Vptr(
@@ -336,6 +713,7 @@ impl Trans {
         let vptr_ctor_def = Def {
             loc,
             name: vptr_ctor_name.clone(),
+            is_exported: false,
             tele: tele.clone(),
             ret: Box::new(Self::wrap_implicit_apps(
                 &tele,
@@ -352,6 +730,17 @@ impl Trans {
             tm_fields.push((m.var.to_string(), Unresolved(loc, None, m.var.clone())));
             ctor_untupled.push(loc, m)
         }
+        let derived_defs = Self::derived_defs(
+            loc,
+            &name,
+            &tele,
+            Derived::Class(
+                name.clone(),
+                Box::new(Unresolved(loc, None, name.ctor())),
+                ty_fields.clone(),
+            ),
+            derived_interfaces,
+        );
         ty_fields.push((
             Var::vptr().to_string(),
             Self::wrap_implicit_apps(&tele, Unresolved(loc, None, name.vptr_type())),
@@ -391,6 +780,7 @@ impl Trans {
         let ctor_def = Def {
             loc,
             name: ctor_name.clone(),
+            is_exported: false,
             tele: ctor_tele,
             ret: Box::new(Unresolved(loc, None, name.clone())),
             body: ctor_body,
@@ -399,6 +789,8 @@ impl Trans {
         let body = Class(Box::new(ClassBody {
             object,
             methods,
+            statics,
+            privates,
             ctor: ctor_name,
             vptr: vptr_name.clone(),
             vptr_ctor: vptr_ctor_name,
@@ -409,6 +801,7 @@ impl Trans {
         let cls_def = Def {
             loc,
             name,
+            is_exported: false,
             tele: tele.clone(),
             ret: Box::new(Univ(loc)),
             body,
@@ -417,6 +810,7 @@ impl Trans {
         let vtbl_def = Def {
             loc,
             name: vtbl_name.clone(),
+            is_exported: false,
             tele: tele.clone(),
             ret: Box::new(Univ(loc)),
             body: VtblType(Object(loc, Box::new(Fields(loc, vtbl_fields)))),
@@ -433,6 +827,7 @@ impl Trans {
         let vtbl_lookup_def = Def {
             loc,
             name: vtbl_lookup_name,
+            is_exported: false,
             tele: lookup_tele,
             ret: Box::new(Self::wrap_implicit_apps(
                 &tele,
@@ -449,7 +844,10 @@ impl Trans {
             vtbl_def,
             vtbl_lookup_def,
         ];
+        defs.extend(static_defs);
         defs.extend(method_defs);
+        defs.extend(implements_defs);
+        defs.extend(derived_defs);
         defs
     }
 
@@ -468,28 +866,77 @@ impl Trans {
         let ret = Box::new(Univ(Loc::from(name_pair.as_span())));
         let name = Var::from(name_pair);
 
-        let alias_pair = pairs.next().unwrap();
-        let alias_loc = Loc::from(alias_pair.as_span());
-        let alias = Var::from(alias_pair);
-
-        let mut im_tele = Tele::default();
+        let mut alias_params = Tele::default();
         let mut fn_defs = Vec::default();
         let mut fns = Vec::default();
+        let mut default_defs = Vec::default();
+        let mut defaults = HashMap::default();
+        let mut supers = Vec::default();
         for p in pairs {
             match p.as_rule() {
-                Rule::row_id => im_tele.push(Self::row_param(p)),
-                Rule::implicit_id => im_tele.push(Self::implicit_param(p)),
-                Rule::interface_fn => {
-                    let mut d = self.fn_postulate(p);
-                    let mut tele = vec![Param {
-                        var: alias.clone(),
+                Rule::interface_param => {
+                    let mut pairs = p.into_inner();
+                    let alias_pair = pairs.next().unwrap();
+                    let alias_loc = Loc::from(alias_pair.as_span());
+                    let mut im_tele = Tele::default();
+                    for p in pairs {
+                        match p.as_rule() {
+                            Rule::row_id => im_tele.push(Self::row_param(p)),
+                            Rule::implicit_id => im_tele.push(Self::implicit_param(p)),
+                            _ => unreachable!(),
+                        }
+                    }
+                    alias_params.push(Param {
+                        var: Var::from(alias_pair),
                         info: Implicit,
                         typ: Box::new(alias_type(alias_loc, &im_tele)),
-                    }];
+                    });
+                }
+                Rule::interface_extends => supers.extend(p.into_inner().map(Var::from)),
+                Rule::interface_type => {
+                    let loc = Loc::from(p.as_span());
+                    let d = Def {
+                        loc,
+                        name: Var::from(p.into_inner().next().unwrap()).assoc_type(&name),
+                        is_exported: false,
+                        tele: alias_params.clone(),
+                        ret: Box::new(Univ(loc)),
+                        body: Findable(name.clone(), alias_params.len()),
+                    };
+                    fns.push(d.name.clone());
+                    fn_defs.push(d);
+                }
+                Rule::interface_fn => {
+                    let has_default = p.clone().into_inner().any(|p| p.as_rule() == Rule::fn_body);
+                    let default = has_default.then(|| self.fn_def(p.clone(), None));
+
+                    let mut d = self.fn_postulate(p);
+                    let mut tele = alias_params.clone();
                     tele.extend(d.tele);
                     d.tele = tele;
+                    d.body = Findable(name.clone(), alias_params.len());
+
+                    if let Some(mut f) = default {
+                        // Defaults find the other methods through the dictionary of the implementation.
+                        let mut tele = alias_params.clone();
+                        tele.push(Param {
+                            var: Var::unbound(),
+                            info: Implicit,
+                            typ: Box::new(ImplementsOf(
+                                loc,
+                                Box::new(Self::wrap_implicit_apps(
+                                    &alias_params,
+                                    Unresolved(loc, None, name.clone()),
+                                )),
+                            )),
+                        });
+                        tele.extend(f.tele);
+                        f.tele = tele;
+                        f.name = d.name.default_func(&name);
+                        defaults.insert(d.name.clone(), f.name.clone());
+                        default_defs.push(f);
+                    }
 
-                    d.body = Findable(name.clone());
                     fns.push(d.name.clone());
                     fn_defs.push(d);
                 }
@@ -500,18 +947,18 @@ impl Trans {
         let mut defs = vec![Def {
             loc,
             name,
-            tele: vec![Param {
-                var: alias,
-                info: Implicit,
-                typ: Box::new(alias_type(alias_loc, &im_tele)),
-            }],
+            is_exported: false,
+            tele: alias_params,
             ret,
             body: Interface {
                 fns,
                 ims: Default::default(),
+                defaults,
+                supers,
             },
         }];
         defs.extend(fn_defs);
+        defs.extend(default_defs);
         defs
     }
 
@@ -520,25 +967,72 @@ impl Trans {
         use Expr::*;
 
         let loc = Loc::from(i.as_span());
-        let mut pairs = i.into_inner();
+        let pairs = i.into_inner();
 
         let mut defs = Vec::default();
 
-        let i = Var::from(pairs.next().unwrap());
-        let im = {
-            let p = pairs.next().unwrap();
-            match p.as_rule() {
-                Rule::tyref => Self::unresolved(p),
-                Rule::primitive_type => self.primitive_type(p),
-                _ => unreachable!(),
-            }
-        };
-
+        let mut i = Var::unbound();
+        let mut tele = Tele::default();
+        let mut preds = Tele::default();
+        let mut ims = Vec::default();
         let mut fns = HashMap::default();
         for p in pairs {
-            let mut def = self.fn_def(p, None);
-            let fn_name = def.name.implement_func(&i, &im);
-            fns.insert(def.name.clone(), fn_name.clone());
+            let is_type = p.as_rule() == Rule::implements_type;
+            let mut def = match p.as_rule() {
+                Rule::row_id => {
+                    tele.push(Self::row_param(p));
+                    continue;
+                }
+                Rule::implicit_id => {
+                    tele.push(Self::implicit_param(p));
+                    continue;
+                }
+                Rule::hkt_param => {
+                    tele.push(Self::hkt_param(p));
+                    continue;
+                }
+                Rule::type_id => {
+                    i = Var::from(p);
+                    continue;
+                }
+                Rule::type_app => {
+                    ims.push(self.type_app(p));
+                    continue;
+                }
+                Rule::primitive_type => {
+                    ims.push(self.primitive_type(p));
+                    continue;
+                }
+                Rule::object_type_ref => {
+                    let loc = Loc::from(p.as_span());
+                    let r = self.row_expr(p.into_inner().next().unwrap());
+                    ims.push(Object(loc, Box::new(r)));
+                    continue;
+                }
+                Rule::enum_type_ref => {
+                    let loc = Loc::from(p.as_span());
+                    let r = self.row_expr(p.into_inner().next().unwrap());
+                    ims.push(Enum(loc, Box::new(r)));
+                    continue;
+                }
+                Rule::pred => {
+                    preds.push(self.pred(p));
+                    continue;
+                }
+                Rule::implements_type => self.implements_type(p),
+                Rule::implements_fn => self.fn_def(p, None),
+                _ => unreachable!(),
+            };
+            let mut fn_tele = tele.clone();
+            fn_tele.extend(preds.clone());
+            fn_tele.extend(def.tele);
+            def.tele = fn_tele;
+            let fn_name = def.name.implement_func(&i, &ims);
+            let i_fn = match is_type {
+                true => def.name.assoc_type(&i),
+                false => def.name.clone(),
+            };
+            fns.insert(i_fn, fn_name.clone());
             def.name = fn_name;
             def.body = match def.body {
                 Fn(f) => ImplementsFn(f),
@@ -546,20 +1040,40 @@ impl Trans {
             };
             defs.push(def);
         }
+        tele.extend(preds);
 
         defs.push(Def {
             loc,
-            name: i.implements(&im),
-            tele: Default::default(),
+            name: i.implements(&ims),
+            is_exported: false,
+            tele,
             ret: Box::new(Univ(loc)),
             body: Implements(Box::new(ImplementsBody {
-                i: (i, Box::new(im)),
+                i: (i, ims),
                 fns,
+                supers: Default::default(),
             })),
         });
         defs
     }
 
+    fn implements_type(&self, t: Pair<Rule>) -> Def<Expr> {
+        use Body::*;
+        use Expr::*;
+        let loc = Loc::from(t.as_span());
+        let mut pairs = t.into_inner();
+        let name = Var::from(pairs.next().unwrap());
+        let typ = self.type_expr(pairs.next().unwrap());
+        Def {
+            loc,
+            name,
+            is_exported: false,
+            tele: Default::default(),
+            ret: Box::new(Univ(loc)),
+            body: Fn(typ),
+        }
+    }
+
     fn const_def(&self, c: Pair<Rule>) -> Def<Expr> {
         use Body::*;
         use Expr::*;
@@ -569,7 +1083,7 @@ impl Trans {
         let mut is_annotated = false;
         for p in c.into_inner() {
             match p.as_rule() {
-                Rule::fn_id => name = Var::from(p),
+                Rule::fn_id | Rule::prop_id => name = Var::from(p),
                 Rule::type_expr => {
                     is_annotated = true;
                     ret = Box::new(self.type_expr(p))
@@ -578,6 +1092,7 @@ impl Trans {
                     return Def {
                         loc,
                         name,
+                        is_exported: false,
                         tele: Default::default(),
                         ret,
                         body: Const(is_annotated, self.expr(p)),
@@ -610,24 +1125,73 @@ impl Trans {
                 unreachable!()
             }
             Rule::primitive_type => self.primitive_type(p),
-            Rule::object_type_ref => Object(
-                loc,
-                Box::new(Self::unresolved(p.into_inner().next().unwrap())),
-            ),
+            Rule::literal_type => Self::literal_type(p),
+            Rule::literal_union => Union(loc, p.into_inner().map(Self::literal_type).collect()),
+            Rule::object_type_ref => {
+                Object(loc, Box::new(self.row_expr(p.into_inner().next().unwrap())))
+            }
             Rule::object_type_literal => Object(loc, Box::new(self.fields(p))),
-            Rule::enum_type_ref => Enum(
-                loc,
-                Box::new(Self::unresolved(p.into_inner().next().unwrap())),
-            ),
+            Rule::enum_type_ref => {
+                Enum(loc, Box::new(self.row_expr(p.into_inner().next().unwrap())))
+            }
             Rule::enum_type_literal => Enum(loc, Box::new(self.fields(p))),
-            Rule::type_app => self.type_app(p),
+            Rule::assoc_type => {
+                let mut pairs = p.into_inner();
+                let i = Var::from(pairs.next().unwrap());
+                let f = Var::from(pairs.next_back().unwrap()).assoc_type(&i);
+                pairs
+                    .map(|arg| {
+                        let loc = Loc::from(arg.as_span());
+                        let (i, e) = match arg.as_rule() {
+                            Rule::row_arg => self.row_arg(arg),
+                            Rule::type_arg => self.type_arg(arg),
+                            _ => unreachable!(),
+                        };
+                        (loc, i, e)
+                    })
+                    .fold(Find(loc, i, f), |a, (loc, i, x)| {
+                        App(loc, Box::new(a), i, Box::new(x))
+                    })
+            }
+            Rule::type_fn_app | Rule::type_app => self.type_app(p),
             Rule::tyref => self.maybe_qualified(p),
             Rule::paren_type_expr => self.type_expr(p.into_inner().next().unwrap()),
+            Rule::type_if => {
+                let mut pairs = p.into_inner();
+                let c = self.expr(pairs.next().unwrap());
+                let t = self.type_expr(pairs.next().unwrap());
+                let e = self.type_expr(pairs.next().unwrap());
+                If(loc, Box::new(c), Box::new(t), Box::new(e))
+            }
+            Rule::type_switch => {
+                let mut pairs = p.into_inner();
+                let e = self.expr(pairs.next().unwrap().into_inner().next().unwrap());
+                Switch(
+                    loc,
+                    Box::new(e),
+                    pairs.map(|c| self.switch_case(c)).collect(),
+                )
+            }
             Rule::hole => Hole(loc),
+            Rule::row_id => Self::unresolved(p),
             _ => unreachable!(),
         }
     }
 
+    fn literal_type(l: Pair<Rule>) -> Expr {
+        use Expr::*;
+        let loc = Loc::from(l.as_span());
+        let p = l.into_inner().next().unwrap();
+        let rule = p.as_rule();
+        let v = p.into_inner().next().unwrap().as_str().to_string();
+        let lit = match rule {
+            Rule::string => Str(loc, v),
+            Rule::number => Num(loc, v),
+            _ => unreachable!(),
+        };
+        Singleton(loc, Box::new(lit))
+    }
+
     fn primitive_type(&self, p: Pair<Rule>) -> Expr {
         use Expr::*;
         let loc = Loc::from(p.as_span());
@@ -659,6 +1223,7 @@ impl Trans {
                 let (i, e) = match arg.as_rule() {
                     Rule::row_arg => self.row_arg(arg),
                     Rule::type_arg => self.type_arg(arg),
+                    Rule::args => (UnnamedExplicit, self.tupled_args(arg)),
                     _ => unreachable!(),
                 };
                 (loc, i, e)
@@ -741,13 +1306,17 @@ impl Trans {
 
     fn row_arg(&self, a: Pair<Rule>) -> (ArgInfo, Expr) {
         let mut p = a.into_inner();
-        let id_or_fields = p.next().unwrap();
-        match id_or_fields.as_rule() {
-            Rule::row_literal => (UnnamedImplicit, self.fields(id_or_fields)),
-            Rule::row_id => (
-                NamedImplicit(Var::from(id_or_fields)),
-                self.fields(p.next().unwrap()),
-            ),
+        let id_or_row = p.next().unwrap();
+        match p.next() {
+            Some(row) => (NamedImplicit(Var::from(id_or_row)), self.row_arg_value(row)),
+            None => (UnnamedImplicit, self.row_arg_value(id_or_row)),
+        }
+    }
+
+    fn row_arg_value(&self, p: Pair<Rule>) -> Expr {
+        match p.as_rule() {
+            Rule::row_literal => self.fields(p),
+            Rule::row_id => Self::unresolved(p),
             _ => unreachable!(),
         }
     }
@@ -769,6 +1338,25 @@ impl Trans {
                     Box::new(self.fn_body(l.next().unwrap())),
                 )
             }
+            Rule::fn_body_fn => {
+                let mut l = p.into_inner();
+                LocalFn(
+                    loc,
+                    Box::new(self.fn_def(l.next().unwrap(), None)),
+                    Box::new(self.fn_body(l.next().unwrap())),
+                )
+            }
+            Rule::fn_body_object_let => {
+                let mut l = p.into_inner();
+                let (fields, rest) = Self::object_pattern(l.next().unwrap());
+                ObjectLet(
+                    loc,
+                    fields,
+                    rest,
+                    Box::new(self.expr(l.next().unwrap())),
+                    Box::new(self.fn_body(l.next().unwrap())),
+                )
+            }
             Rule::fn_body_unit_let => {
                 let mut l = p.into_inner();
                 UnitLet(
@@ -913,6 +1501,20 @@ impl Trans {
                 let n = pairs.next().unwrap().as_str().to_string();
                 App(loc, Box::new(Access(loc, n)), UnnamedExplicit, Box::new(a))
             }
+            Rule::object_optional_access => {
+                let mut pairs = p.into_inner();
+                let a = self.object_operand(pairs.next().unwrap());
+                let n = pairs.next().unwrap().as_str().to_string();
+                OptionalAccess(loc, Box::new(a), n)
+            }
+            Rule::object_omit => self.object_omit(p),
+            Rule::object_map => {
+                let mut pairs = p.into_inner();
+                let a = self.object_operand(pairs.next().unwrap());
+                let f = self.maybe_qualified(pairs.next().unwrap());
+                MapMethod(loc, Box::new(a), Box::new(f))
+            }
+            Rule::object_update => self.object_update(p),
             Rule::object_cast => Downcast(
                 loc,
                 Box::new(self.object_operand(p.into_inner().next().unwrap())),
@@ -925,22 +1527,11 @@ impl Trans {
             Rule::enum_switch => {
                 let mut pairs = p.into_inner();
                 let e = self.expr(pairs.next().unwrap().into_inner().next().unwrap());
-                let mut cases = Vec::default();
-                for p in pairs {
-                    let mut c = p.into_inner();
-                    let n = c.next().unwrap().as_str().to_string();
-                    let mut v = None;
-                    let mut body = None;
-                    for p in c {
-                        match p.as_rule() {
-                            Rule::param_id => v = Some(Var::from(p)),
-                            Rule::expr => body = Some(self.expr(p)),
-                            _ => unreachable!(),
-                        };
-                    }
-                    cases.push((n, v.unwrap_or(Var::unbound()), body.unwrap()));
-                }
-                Switch(loc, Box::new(e), cases)
+                Switch(
+                    loc,
+                    Box::new(e),
+                    pairs.map(|c| self.switch_case(c)).collect(),
+                )
             }
             Rule::lambda_expr => {
                 let pairs = p.into_inner();
@@ -1056,8 +1647,18 @@ impl Trans {
         let mut i = p.into_inner();
         let id = i.next().unwrap();
         match id.as_rule() {
-            Rule::qualifier => {
-                Unresolved(loc, Some(self.qualifier(id)), Var::from(i.next().unwrap()))
+            Rule::qualifier => match self.maybe_qualified(i.next().unwrap()) {
+                Unresolved(_, None, v) => Unresolved(loc, Some(self.qualifier(id)), v),
+                _ => unreachable!(),
+            },
+            Rule::static_ref => {
+                let mut pairs = id.into_inner();
+                let cls = Var::from(pairs.next().unwrap());
+                Unresolved(
+                    loc,
+                    None,
+                    cls.static_member(Var::from(pairs.next().unwrap())),
+                )
             }
             _ => Unresolved(loc, None, Var::from(id)),
         }
@@ -1144,19 +1745,53 @@ impl Trans {
         for pair in p.into_inner() {
             let mut f = pair.into_inner();
             let id = f.next().unwrap().as_str().to_string();
-            let typ = f.next().map_or(Unit(loc), |e| self.type_expr(e));
+            let typ = match f.next() {
+                Some(ps) if ps.as_rule() == Rule::variant_params => self.variant_params(ps),
+                Some(o) if o.as_rule() == Rule::optional => {
+                    let o_loc = Loc::from(o.as_span());
+                    Optional(o_loc, Box::new(self.type_expr(f.next().unwrap())))
+                }
+                Some(e) => self.type_expr(e),
+                None => Unit(loc),
+            };
             fields.push((id, typ));
         }
 
         Fields(loc, fields)
     }
 
+    fn variant_params(&self, ps: Pair<Rule>) -> Expr {
+        let loc = Loc::from(ps.as_span());
+        let mut untupled = UntupledParams::new(loc);
+        for p in ps.into_inner() {
+            let p_loc = Loc::from(p.as_span());
+            let mut pairs = p.into_inner();
+            let first = pairs.next().unwrap();
+            let (var, typ) = match pairs.next() {
+                Some(t) => (Var::from(first), t),
+                None => (Var::unbound(), first),
+            };
+            let param = Param {
+                var,
+                info: Explicit,
+                typ: Box::new(self.type_expr(typ)),
+            };
+            untupled.push(p_loc, param);
+        }
+        match untupled.1.len() {
+            1 => *untupled.1.pop().unwrap().1.typ,
+            _ => *Param::from(untupled).typ,
+        }
+    }
+
     fn label(&self, l: Pair<Rule>) -> (String, Expr) {
         let mut p = l.into_inner();
-        (
-            p.next().unwrap().as_str().to_string(),
-            self.expr(p.next().unwrap()),
-        )
+        let id = p.next().unwrap();
+        let n = id.as_str().to_string();
+        let e = p
+            .next()
+            .map_or_else(|| Self::unresolved(id), |e| self.expr(e));
+        (n, e)
     }
 
     fn object_literal(&self, l: Pair<Rule>) -> Expr {
@@ -1168,9 +1803,30 @@ impl Trans {
         )
     }
 
+    fn object_update(&self, u: Pair<Rule>) -> Expr {
+        use Expr::*;
+        let loc = Loc::from(u.as_span());
+        let mut pairs = u.into_inner();
+        let a = self.object_operand(pairs.next().unwrap());
+        let f = Fields(loc, pairs.map(|e| self.label(e)).collect());
+        Update(loc, Box::new(a), Box::new(f))
+    }
+
+    fn object_omit(&self, o: Pair<Rule>) -> Expr {
+        use Expr::*;
+        let loc = Loc::from(o.as_span());
+        let mut pairs = o.into_inner();
+        let a = self.object_operand(pairs.next().unwrap());
+        let names = pairs
+            .map(|p| p.into_inner().next().unwrap().as_str().to_string())
+            .collect();
+        Restrict(loc, Box::new(a), names)
+    }
+
     fn object_operand(&self, o: Pair<Rule>) -> Expr {
         let p = o.into_inner().next().unwrap();
         match p.as_rule() {
+            Rule::object_omit => self.object_omit(p),
             Rule::app => self.app(p, None),
             Rule::object_literal => self.object_literal(p),
             Rule::idref => self.maybe_qualified(p),
@@ -1184,9 +1840,11 @@ impl Trans {
         let loc = Loc::from(v.as_span());
         let mut pairs = v.into_inner();
         let n = pairs.next().unwrap().as_str().to_string();
-        let a = pairs
-            .next()
-            .map_or(TT(loc), |p| self.expr(p.into_inner().next().unwrap()));
+        let a = pairs.next().map_or(TT(loc), |p| match p.as_rule() {
+            Rule::paren_expr => self.expr(p.into_inner().next().unwrap()),
+            Rule::args => self.tupled_args(p),
+            _ => unreachable!(),
+        });
         Variant(loc, n, Box::new(a))
     }
 
@@ -1201,6 +1859,38 @@ impl Trans {
         }
     }
 
+    fn switch_case(&self, c: Pair<Rule>) -> (String, Var, Expr) {
+        use Expr::*;
+
+        let c_loc = Loc::from(c.as_span());
+        let mut pairs = c.into_inner();
+        let n = pairs.next().unwrap().as_str().to_string();
+        let mut vars = Vec::default();
+        let mut body = None;
+        for p in pairs {
+            match p.as_rule() {
+                Rule::param_id => vars.push(Self::unresolved(p)),
+                Rule::expr => body = Some(self.expr(p)),
+                Rule::type_expr => body = Some(self.type_expr(p)),
+                _ => unreachable!(),
+            };
+        }
+        let body = body.unwrap();
+        let (v, body) = match vars.len() {
+            0 => (Var::unbound(), body),
+            1 => match vars.pop().unwrap() {
+                Unresolved(_, _, v) => (v, body),
+                _ => unreachable!(),
+            },
+            _ => {
+                let v = Var::tupled();
+                let body = Expr::wrap_tuple_lets(c_loc, &v, vars, body);
+                (v, body)
+            }
+        };
+        (n, v, body)
+    }
+
     fn tupled_args(&self, a: Pair<Rule>) -> Expr {
         use Expr::*;
         let loc = Loc::from(a.as_span());
@@ -1212,6 +1902,24 @@ impl Trans {
             .rfold(TT(loc), |a, (loc, x)| Tuple(loc, Box::new(x), Box::new(a)))
     }
 
+    fn object_pattern(p: Pair<Rule>) -> (Vec<(String, Var)>, Option<Var>) {
+        let mut fields = Vec::default();
+        let mut rest = None;
+        for p in p.into_inner() {
+            match p.as_rule() {
+                Rule::pattern_label => {
+                    let mut pairs = p.into_inner();
+                    let id = pairs.next().unwrap();
+                    let n = id.as_str().to_string();
+                    fields.push((n, Var::from(pairs.next().unwrap_or(id))));
+                }
+                Rule::pattern_rest => rest = Some(Var::from(p.into_inner().next().unwrap())),
+                _ => unreachable!(),
+            }
+        }
+        (fields, rest)
+    }
+
     fn partial_let(&self, pairs: &mut Pairs<Rule>) -> (Var, Option<Box<Expr>>, Expr) {
         let id = Var::from(pairs.next().unwrap());
         let mut typ = None;
@@ -1239,6 +1947,13 @@ impl UntupledParams {
         self.1.push((loc, param))
     }
 
+    /// Checks whether the parameters are referred to in `t`, e.g. by a dependent return type.
+    fn is_mentioned(&self, t: &Pair<Rule>) -> bool {
+        t.clone().into_inner().flatten().any(|p| {
+            p.as_rule() == Rule::idref && self.1.iter().any(|(_, q)| q.var.as_str() == p.as_str())
+        })
+    }
+
     fn unresolved(&self) -> Vec<Expr> {
         use Expr::*;
         self.1
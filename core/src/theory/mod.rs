@@ -99,10 +99,22 @@ impl Var {
         Self::new(format!("{self}__{m}"))
     }
 
+    pub fn static_member(&self, m: Self) -> Self {
+        Self::new(format!("{self}__static__{m}"))
+    }
+
     pub fn ctor(&self) -> Self {
         Self::new(format!("{self}{CTOR}"))
     }
 
+    pub fn getter(&self) -> Self {
+        Self::new(format!("get__{self}"))
+    }
+
+    pub fn setter(&self) -> Self {
+        Self::new(format!("set__{self}"))
+    }
+
     pub fn vptr() -> Self {
         Self::new(VPTR)
     }
@@ -123,12 +135,31 @@ impl Var {
         Self::new(format!("{self}{VTBL_LOOKUP}"))
     }
 
-    pub fn implements(&self, im: &Expr) -> Self {
-        Self::new(format!("{self}__for__{im}"))
+    pub fn implements(&self, ims: &[Expr]) -> Self {
+        Self::new(format!("{self}__for__{}", Self::implementors(ims)))
+    }
+
+    pub fn implement_func(&self, i: &Self, ims: &[Expr]) -> Self {
+        Self::new(format!("{i}__for__{}__{self}", Self::implementors(ims)))
+    }
+
+    fn implementors(ims: &[Expr]) -> String {
+        ims.iter()
+            .map(|im| im.to_string())
+            .collect::<Vec<_>>()
+            .join("__")
+    }
+
+    pub fn default_func(&self, i: &Self) -> Self {
+        Self::new(format!("{i}__default__{self}"))
+    }
+
+    pub fn assoc_type(&self, i: &Self) -> Self {
+        Self::new(format!("{i}__{self}"))
     }
 
-    pub fn implement_func(&self, i: &Self, im: &Expr) -> Self {
-        Self::new(format!("{i}__for__{im}__{self}"))
+    pub fn super_dict(&self) -> String {
+        format!("__super_{self}")
     }
 
     pub fn id(&self) -> usize {